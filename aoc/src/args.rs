@@ -2,9 +2,11 @@
 //! (No need for full-blow crate like clap)
 
 use std::env;
+use std::str::FromStr;
 
 const DEBUG_FLAG: &str = "-d";
 const VERBOSE_FLAG: &str = "-v";
+const QUIET_FLAG: &str = "-q";
 
 pub fn is_debug() -> bool {
     has_arg(DEBUG_FLAG)
@@ -14,6 +16,88 @@ pub fn is_verbose() -> bool {
     has_arg(VERBOSE_FLAG)
 }
 
+/// Suppress all the non-essential output (warnings, timings),
+/// leaving only the answers.
+pub fn quiet() -> bool {
+    has_arg(QUIET_FLAG)
+}
+
 pub fn has_arg(s: &str) -> bool {
-    env::args().any(|a| a == s)
+    has_arg_from(env::args(), s)
+}
+
+fn has_arg_from(mut args: impl Iterator<Item = String>, s: &str) -> bool {
+    args.any(|a| a == s)
+}
+
+/// Get the value of an optional "--name=value" argument.
+/// Panics if the value can't be parsed into T.
+pub fn arg_value<T: FromStr>(name: &str) -> Option<T> {
+    arg_value_from(env::args(), name)
+}
+
+/// Get the value of a mandatory "--name=value" argument.
+/// Panics if the argument is missing or can't be parsed into T.
+pub fn require_value<T: FromStr>(name: &str) -> T {
+    require_value_from(env::args(), name)
+}
+
+fn arg_value_from<T: FromStr>(mut args: impl Iterator<Item = String>, name: &str) -> Option<T> {
+    let prefix = format!("--{name}=");
+    let arg = args.find(|a| a.starts_with(&prefix))?;
+    let value = &arg[prefix.len()..];
+    match T::from_str(value) {
+        Ok(v) => Some(v),
+        Err(_) => panic!("invalid value for argument --{name}: \"{value}\""),
+    }
+}
+
+fn require_value_from<T: FromStr>(args: impl Iterator<Item = String>, name: &str) -> T {
+    match arg_value_from(args, name) {
+        Some(v) => v,
+        None => panic!("missing required argument --{name}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fake_args<'a>(a: &'a [&str]) -> impl Iterator<Item = String> + 'a {
+        a.iter().map(|s| s.to_string())
+    }
+
+    #[test]
+    fn args_quiet() {
+        assert!(has_arg_from(fake_args(&["day_22", "-q"]), QUIET_FLAG));
+        assert!(has_arg_from(fake_args(&["day_22", "-d", "-q"]), QUIET_FLAG));
+        assert!(!has_arg_from(fake_args(&["day_22", "-d"]), QUIET_FLAG));
+        assert!(!has_arg_from(fake_args(&["day_22", "--q"]), QUIET_FLAG));
+    }
+
+    #[test]
+    fn args_value() {
+        let args = ["day_11", "-d", "--steps=75", "--name=abc"];
+        assert_eq!(arg_value_from::<usize>(fake_args(&args), "steps"), Some(75));
+        assert_eq!(
+            arg_value_from::<String>(fake_args(&args), "name"),
+            Some("abc".to_string())
+        );
+        assert_eq!(arg_value_from::<usize>(fake_args(&args), "size"), None);
+        assert_eq!(require_value_from::<usize>(fake_args(&args), "steps"), 75);
+    }
+
+    #[test]
+    #[should_panic(expected = "missing required argument --size")]
+    fn args_require_missing() {
+        let args = ["day_11", "--steps=75"];
+        require_value_from::<usize>(fake_args(&args), "size");
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid value for argument --steps")]
+    fn args_value_invalid() {
+        let args = ["day_11", "--steps=many"];
+        arg_value_from::<usize>(fake_args(&args), "steps");
+    }
 }
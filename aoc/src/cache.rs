@@ -0,0 +1,82 @@
+//! Memoization of recursive computations.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Cache of the values already computed for each key.
+/// The compute function receives the Memo itself, so that a
+/// recursive computation can reuse it for its sub-problems.
+#[derive(Clone, Debug)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+}
+
+impl<K: Eq + Hash, V: Clone> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Memo {
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Return the cached value for key, or compute it with f()
+    /// and cache it.
+    pub fn get_or_compute(&mut self, key: K, f: impl FnOnce(&mut Self) -> V) -> V {
+        if let Some(v) = self.cache.get(&key) {
+            return v.clone();
+        }
+        let v = f(self);
+        self.cache.insert(key, v.clone());
+        v
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.cache.get(key)
+    }
+
+    /// Number of cached values.
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fibonacci(n: u64, memo: &mut Memo<u64, u64>, calls: &mut usize) -> u64 {
+        *calls += 1;
+        memo.get_or_compute(n, |memo| {
+            if n < 2 {
+                n
+            } else {
+                fibonacci(n - 1, memo, calls) + fibonacci(n - 2, memo, calls)
+            }
+        })
+    }
+
+    #[test]
+    fn memo_fibonacci() {
+        let mut memo = Memo::new();
+        let mut calls = 0;
+        assert!(memo.is_empty());
+        assert_eq!(fibonacci(90, &mut memo, &mut calls), 2880067194370816120);
+        // every value from 0 to 90 is cached, each computed only once
+        assert_eq!(memo.len(), 91);
+        assert_eq!(memo.get(&10), Some(&55));
+        assert_eq!(calls, 1 + 2 * 89);
+
+        calls = 0;
+        assert_eq!(fibonacci(50, &mut memo, &mut calls), 12586269025);
+        assert_eq!(calls, 1);
+    }
+}
@@ -61,3 +61,76 @@ pub const BG_BRIGHT_COLORS: [&str; 8] = [
     "\x1B[106m",
     "\x1B[107m",
 ];
+
+/// Background colors from the coldest to the hottest value.
+pub const HEAT_SCALE: [&str; 6] = [
+    BG_COLORS[BLUE],
+    BG_COLORS[CYAN],
+    BG_COLORS[GREEN],
+    BG_COLORS[YELLOW],
+    BG_COLORS[RED],
+    BG_COLORS[MAGENTA],
+];
+
+/// Background color of a value in the heat scale between min and max.
+/// Values out of the range are clamped.
+pub fn heat_color(value: usize, min: usize, max: usize) -> &'static str {
+    let value = value.clamp(min, max);
+    let range = max - min;
+    if range == 0 {
+        return HEAT_SCALE[0];
+    }
+    HEAT_SCALE[(value - min) * (HEAT_SCALE.len() - 1) / range]
+}
+
+/// Truecolor (24 bits) foreground escape of a value between min
+/// and max, interpolated from pure blue (min) to pure red (max).
+/// Values out of the range are clamped.
+pub fn heatmap_fg(value: f64, min: f64, max: f64) -> String {
+    let t = if max > min {
+        ((value - min) / (max - min)).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let red = (t * 255.0).round() as u8;
+    format!("\x1B[38;2;{};0;{}m", red, 255 - red)
+}
+
+/// Returns false if the NO_COLOR environment variable is set
+/// (see https://no-color.org/)
+pub fn use_colors() -> bool {
+    std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn heat_color_scale() {
+        assert_eq!(heat_color(10, 10, 60), BG_COLORS[BLUE]);
+        assert_eq!(heat_color(0, 10, 60), BG_COLORS[BLUE]);
+        assert_eq!(heat_color(30, 10, 60), BG_COLORS[GREEN]);
+        assert_eq!(heat_color(60, 10, 60), BG_COLORS[MAGENTA]);
+        assert_eq!(heat_color(usize::MAX, 10, 60), BG_COLORS[MAGENTA]);
+        assert_eq!(heat_color(5, 5, 5), BG_COLORS[BLUE]);
+    }
+
+    #[test]
+    fn heatmap_fg_gradient() {
+        assert_eq!(heatmap_fg(1.0, 1.0, 3.0), "\x1B[38;2;0;0;255m");
+        assert_eq!(heatmap_fg(3.0, 1.0, 3.0), "\x1B[38;2;255;0;0m");
+        assert_eq!(heatmap_fg(-8.0, 1.0, 3.0), heatmap_fg(1.0, 1.0, 3.0));
+
+        let mid = heatmap_fg(2.0, 1.0, 3.0);
+        let rgb: Vec<u8> = mid
+            .trim_start_matches("\x1B[38;2;")
+            .trim_end_matches('m')
+            .split(';')
+            .map(|v| v.parse().unwrap())
+            .collect();
+        assert!(rgb[0] > 0 && rgb[0] < 255);
+        assert!(rgb[2] > 0 && rgb[2] < 255);
+        assert_eq!(rgb[0] as usize + rgb[2] as usize, 255);
+    }
+}
@@ -1,6 +1,6 @@
 //! Dijktstra algorithm for shortest path finding
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::hash::Hash;
 
 // Define an interface for a Dijkstra algo client
@@ -19,6 +19,13 @@ pub trait DijkstraController {
     // (or return a non-existant node if you want to map all the graph)
     fn get_target_node(&self) -> Self::Node;
 
+    // Return true if the node is a destination.
+    // By default only the node from get_target_node(), but it can be
+    // overridden to stop at the first node satisfying any predicate.
+    fn is_target(&self, node: &Self::Node) -> bool {
+        *node == self.get_target_node()
+    }
+
     // Return a list of neighbors from a node,
     // along with their distance from it.
     // The controller may returned neighbors that have already
@@ -26,6 +33,14 @@ pub trait DijkstraController {
     // as needed.
     fn get_neighbors_distances(&self, node: &Self::Node) -> Vec<(Self::Node, usize)>;
 
+    // Cost of entering a node, added to the distance of each edge
+    // leading to it (the start node cost is never counted).
+    // Controllers where the weight is on the cells rather than on
+    // the moves can return edges of distance 0 and the cell value here.
+    fn node_cost(&self, _node: &Self::Node) -> usize {
+        0
+    }
+
     // This function will be called for each node that have been finalized
     // and have a known minimal distance from the start, along with their
     // previous node (if not unique, it will be arbitrary).
@@ -65,8 +80,6 @@ pub fn dijkstra<T: DijkstraController>(controller: &mut T, explore_all: bool) ->
 
     unvisited_frontier.insert(controller.get_starting_node(), (0, None));
 
-    let target_node = controller.get_target_node();
-
     let mut found_distance = None;
 
     // Follow dijkstra algo
@@ -90,8 +103,9 @@ pub fn dijkstra<T: DijkstraController>(controller: &mut T, explore_all: bool) ->
         finalized_nodes.insert(current_node);
         controller.mark_visited_distance(current_node, current_distance, previous_node);
 
-        if current_node == target_node {
-            found_distance = Some(current_distance);
+        if controller.is_target(&current_node) {
+            // keep the nearest target when exploring all
+            found_distance.get_or_insert(current_distance);
             if !explore_all {
                 return current_distance;
             }
@@ -105,7 +119,7 @@ pub fn dijkstra<T: DijkstraController>(controller: &mut T, explore_all: bool) ->
                 continue;
             }
             // distance to "node" via "current_node"
-            let path_total_distance = dist + current_distance;
+            let path_total_distance = dist + controller.node_cost(&next_node) + current_distance;
             if let Some((prev_dist, prev_node)) = unvisited_frontier.get_mut(&next_node) {
                 // Update the best distance which was already known,
                 // and from a better "previous node" (different path)
@@ -128,6 +142,128 @@ pub fn dijkstra<T: DijkstraController>(controller: &mut T, explore_all: bool) ->
     }
 }
 
+// Explore all the nodes within "max_dist" of the start, ignoring the
+// target node. Nodes farther than the bound are never expanded.
+// Returns the minimal distance of each node within the bound.
+pub fn dijkstra_bounded<T: DijkstraController>(
+    controller: &mut T,
+    max_dist: usize,
+) -> HashMap<T::Node, usize> {
+    let mut finalized_nodes = HashMap::<T::Node, usize>::new();
+    let mut unvisited_frontier = HashMap::<T::Node, (usize, Option<T::Node>)>::new();
+
+    unvisited_frontier.insert(controller.get_starting_node(), (0, None));
+
+    while let Some((&shortest_node, _)) =
+        unvisited_frontier.iter().min_by(|a, b| a.1 .0.cmp(&b.1 .0))
+    {
+        let (current_distance, previous_node) = unvisited_frontier.remove(&shortest_node).unwrap();
+
+        finalized_nodes.insert(shortest_node, current_distance);
+        controller.mark_visited_distance(shortest_node, current_distance, previous_node);
+
+        for (next_node, dist) in controller.get_neighbors_distances(&shortest_node) {
+            let path_total_distance = dist + controller.node_cost(&next_node) + current_distance;
+            if finalized_nodes.contains_key(&next_node) || path_total_distance > max_dist {
+                continue;
+            }
+            let entry = unvisited_frontier
+                .entry(next_node)
+                .or_insert((path_total_distance, Some(shortest_node)));
+            if path_total_distance < entry.0 {
+                *entry = (path_total_distance, Some(shortest_node));
+            }
+        }
+    }
+
+    finalized_nodes
+}
+
+// Breadth-first search for graphs where all edges have the same cost:
+// the distances returned by get_neighbors_distances() are ignored and
+// each edge counts as 1. Nodes are marked visited with their BFS layer.
+// Returns the distance of the target, or usize::MAX if unreachable.
+pub fn bfs<T: DijkstraController>(controller: &mut T) -> usize {
+    let mut seen = HashSet::<T::Node>::new();
+    let mut frontier = VecDeque::<(T::Node, usize, Option<T::Node>)>::new();
+
+    let start = controller.get_starting_node();
+    seen.insert(start);
+    frontier.push_back((start, 0, None));
+
+    while let Some((current_node, current_distance, previous_node)) = frontier.pop_front() {
+        controller.mark_visited_distance(current_node, current_distance, previous_node);
+
+        if controller.is_target(&current_node) {
+            return current_distance;
+        }
+
+        for (next_node, _) in controller.get_neighbors_distances(&current_node) {
+            if seen.insert(next_node) {
+                frontier.push_back((next_node, current_distance + 1, Some(current_node)));
+            }
+        }
+    }
+
+    // Not an error for callers probing reachability (like day 18
+    // bisection), so only reported when debugging.
+    if crate::args::is_debug() {
+        eprintln!("BFS finished exploring all nodes without reaching target !");
+    }
+    usize::MAX
+}
+
+// Adapter to run dijkstra() from plain closures.
+struct FnController<N, F, G> {
+    start: N,
+    is_target: F,
+    neighbors: G,
+}
+
+impl<N, F, G> DijkstraController for FnController<N, F, G>
+where
+    N: Copy + Eq + Hash,
+    F: Fn(&N) -> bool,
+    G: Fn(&N) -> Vec<(N, usize)>,
+{
+    type Node = N;
+
+    fn get_starting_node(&self) -> N {
+        self.start
+    }
+
+    fn get_target_node(&self) -> N {
+        // never used, is_target() is overridden
+        self.start
+    }
+
+    fn is_target(&self, node: &N) -> bool {
+        (self.is_target)(node)
+    }
+
+    fn get_neighbors_distances(&self, node: &N) -> Vec<(N, usize)> {
+        (self.neighbors)(node)
+    }
+
+    fn mark_visited_distance(&mut self, _node: N, _distance: usize, _previous: Option<N>) {}
+}
+
+// Same as dijkstra() for throwaway graphs, without writing a
+// DijkstraController: the graph is only given by closures.
+// Returns the distance to the nearest target, or usize::MAX.
+pub fn dijkstra_fn<N: Copy + Eq + Hash>(
+    start: N,
+    is_target: impl Fn(&N) -> bool,
+    neighbors: impl Fn(&N) -> Vec<(N, usize)>,
+) -> usize {
+    let mut controller = FnController {
+        start,
+        is_target,
+        neighbors,
+    };
+    dijkstra(&mut controller, false)
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -212,7 +348,24 @@ mod test {
         assert_eq!(graph.path, expected_paths);
     }
 
+    #[test]
+    fn closure_dijkstra() {
+        // 'a' ->(7) 'b' ->(1) 'd'
+        //     ->(2) 'c' ->(3) 'b'
+        //               ->(9) 'd'
+        let edges = |n: &char| match n {
+            'a' => vec![('b', 7), ('c', 2)],
+            'b' => vec![('d', 1)],
+            'c' => vec![('b', 3), ('d', 9)],
+            _ => vec![],
+        };
+        assert_eq!(dijkstra_fn('a', |n| *n == 'd', edges), 6);
+        assert_eq!(dijkstra_fn('a', |n| *n == 'b' || *n == 'c', edges), 2);
+        assert_eq!(dijkstra_fn('d', |n| *n == 'a', edges), usize::MAX);
+    }
+
     use crate::grid::{Grid, GridBuilder};
+    use crate::testutil::assert_grid_eq;
 
     struct GridCost {
         cost: Grid<usize>,
@@ -285,22 +438,6 @@ mod test {
         path.set(node.0, node.1, 'S');
     }
 
-    fn grids_equal(g1: &Grid<char>, g2: &Grid<char>) -> bool {
-        if g1.width != g2.width || g1.height != g2.height {
-            return false;
-        }
-
-        for y in 0..g1.height {
-            let r1 = g1.get_row_slice(y);
-            let r2 = g2.get_row_slice(y);
-            if r1 != r2 {
-                return false;
-            }
-        }
-
-        true
-    }
-
     #[test]
     fn grid_maze_dijkstra() {
         let map = vec![
@@ -336,13 +473,6 @@ mod test {
         ];
 
         let expected_d = 48;
-        let mut gb = GridBuilder::<char>::new();
-        for row in pat {
-            let line: Vec<char> = row.chars().collect();
-            gb.append_line(&line);
-        }
-
-        let expected_path = gb.to_grid();
 
         let mut gb = GridBuilder::<usize>::new();
         for row in map {
@@ -365,6 +495,179 @@ mod test {
 
         path.pretty_print();
         assert_eq!(d, expected_d);
-        assert!(grids_equal(&path, &expected_path));
+        assert_grid_eq(&path, &pat.join("\n"));
+    }
+
+    // Same as GridCost, but with the weight on the cells.
+    struct GridNodeCost {
+        cost: Grid<usize>,
+    }
+
+    impl DijkstraController for GridNodeCost {
+        type Node = (usize, usize);
+
+        fn get_starting_node(&self) -> Self::Node {
+            (0, 0)
+        }
+
+        fn get_target_node(&self) -> Self::Node {
+            (self.cost.width - 1, self.cost.height - 1)
+        }
+
+        fn get_neighbors_distances(&self, node: &Self::Node) -> Vec<(Self::Node, usize)> {
+            self.cost
+                .neighbors4(node.0, node.1)
+                .into_iter()
+                .map(|n| (n, 0))
+                .collect()
+        }
+
+        fn node_cost(&self, node: &Self::Node) -> usize {
+            self.cost.get(node.0, node.1)
+        }
+
+        fn mark_visited_distance(
+            &mut self,
+            _node: Self::Node,
+            _distance: usize,
+            _previous: Option<Self::Node>,
+        ) {
+        }
+    }
+
+    #[test]
+    fn grid_maze_node_cost_dijkstra() {
+        let map = [
+            "0493432911123",
+            "0195450909123",
+            "2255240909054",
+            "1446580909052",
+            "4546650909036",
+            "1438510909054",
+            "4457809909066",
+            "3637810909053",
+            "4654961909187",
+            "4564672909193",
+            "1224680909193",
+            "2546540909191",
+            "4322671119993",
+        ];
+        let mut gb = GridBuilder::<usize>::new();
+        for row in map {
+            gb.append_char_map(row);
+        }
+        let mut graph = GridNodeCost { cost: gb.to_grid() };
+
+        // same as grid_maze_dijkstra
+        assert_eq!(dijkstra(&mut graph, false), 48);
+    }
+
+    #[test]
+    fn grid_uniform_bfs() {
+        let (width, height) = (5, 4);
+        let mut graph = GridCost {
+            cost: Grid::<usize>::new(width, height, 1),
+            path: Grid::<(usize, Option<(usize, usize)>)>::new(width, height, (999, None)),
+        };
+
+        // Manhattan distance from corner to corner
+        let d = bfs(&mut graph);
+        assert_eq!(d, 4 + 3);
+        assert_eq!(graph.path.get(0, 0), (0, None));
+        assert_eq!(graph.path.get(2, 1).0, 3);
+
+        let mut costly = GridCost {
+            cost: Grid::<usize>::new(width, height, 9),
+            path: Grid::<(usize, Option<(usize, usize)>)>::new(width, height, (999, None)),
+        };
+        assert_eq!(bfs(&mut costly), d);
+    }
+
+    #[test]
+    fn grid_bounded_dijkstra() {
+        let mut gb = GridBuilder::<usize>::new();
+        for row in ["1191", "1111", "5111"] {
+            gb.append_char_map(row);
+        }
+        let costmap = gb.to_grid();
+        let (width, height) = (costmap.width, costmap.height);
+        let mut graph = GridCost {
+            cost: costmap,
+            path: Grid::<(usize, Option<(usize, usize)>)>::new(width, height, (999, None)),
+        };
+
+        let reached = dijkstra_bounded(&mut graph, 3);
+        let mut expected = HashMap::<(usize, usize), usize>::new();
+        for (node, d) in [
+            ((0, 0), 0),
+            ((1, 0), 1),
+            ((0, 1), 1),
+            ((1, 1), 2),
+            ((2, 1), 3),
+            ((1, 2), 3),
+        ] {
+            expected.insert(node, d);
+        }
+        assert_eq!(reached, expected);
+        // not reached: (0,2) costs 5, (2,0) costs 9
+        assert_eq!(graph.path.get(0, 2).0, 999);
+        assert_eq!(graph.path.get(2, 1).0, 3);
+    }
+
+    // Same graph as BasicGraph but stops at any node of a set.
+    struct AnyTarget {
+        graph: BasicGraph,
+        targets: HashSet<usize>,
+    }
+
+    impl DijkstraController for AnyTarget {
+        type Node = usize;
+
+        fn get_starting_node(&self) -> Self::Node {
+            self.graph.get_starting_node()
+        }
+
+        fn get_target_node(&self) -> Self::Node {
+            // not used
+            usize::MAX
+        }
+
+        fn is_target(&self, node: &Self::Node) -> bool {
+            self.targets.contains(node)
+        }
+
+        fn get_neighbors_distances(&self, node: &Self::Node) -> Vec<(Self::Node, usize)> {
+            self.graph.get_neighbors_distances(node)
+        }
+
+        fn mark_visited_distance(
+            &mut self,
+            node: Self::Node,
+            distance: usize,
+            previous: Option<Self::Node>,
+        ) {
+            self.graph.mark_visited_distance(node, distance, previous);
+        }
+    }
+
+    #[test]
+    fn dijkstra_any_target() {
+        // 0 ->(1) 1 ->(5) 3
+        //   ->(10) 2
+        let mut any = AnyTarget {
+            graph: BasicGraph {
+                graph: vec![vec![(1, 1), (2, 10)], vec![(3, 5)], vec![], vec![]],
+                path: HashMap::<usize, usize>::new(),
+            },
+            targets: HashSet::from([2, 3]),
+        };
+        assert_eq!(dijkstra(&mut any, false), 6);
+        assert!(!any.graph.path.contains_key(&2));
+
+        any.targets = HashSet::from([2]);
+        assert_eq!(dijkstra(&mut any, false), 10);
+        // BFS counts the edges only
+        any.targets = HashSet::from([2, 3]);
+        assert_eq!(bfs(&mut any), 1);
     }
 }
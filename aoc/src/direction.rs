@@ -0,0 +1,108 @@
+//! The 4 orthogonal directions of movement on a grid.
+
+use crate::grid::Grid;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+use Direction::*;
+
+impl Direction {
+    /// All directions, clockwise from Up.
+    pub const ALL: [Direction; 4] = [Up, Right, Down, Left];
+
+    /// Turn 90 degrees clockwise
+    pub fn rotate_right(&self) -> Direction {
+        match self {
+            Left => Up,
+            Up => Right,
+            Right => Down,
+            Down => Left,
+        }
+    }
+
+    /// Turn 90 degrees counter-clockwise
+    pub fn rotate_left(&self) -> Direction {
+        match self {
+            Left => Down,
+            Down => Right,
+            Right => Up,
+            Up => Left,
+        }
+    }
+
+    /// Coordinates of the next cell from pos in this direction,
+    /// or None if it would go out of the grid.
+    pub fn step<T>(&self, pos: (usize, usize), grid: &Grid<T>) -> Option<(usize, usize)> {
+        self.step_bounded(pos, grid.width, grid.height)
+    }
+
+    /// Same as step() for a grid of the given dimensions.
+    pub fn step_bounded(
+        &self,
+        (x, y): (usize, usize),
+        width: usize,
+        height: usize,
+    ) -> Option<(usize, usize)> {
+        match self {
+            Left if x > 0 => Some((x - 1, y)),
+            Right if x + 1 < width => Some((x + 1, y)),
+            Up if y > 0 => Some((x, y - 1)),
+            Down if y + 1 < height => Some((x, y + 1)),
+            _ => None,
+        }
+    }
+
+    /// A distinct single bit for each direction,
+    /// to store a set of directions in a u8 bitmap.
+    pub fn bit(&self) -> u8 {
+        match self {
+            Up => 0b0001,
+            Right => 0b0010,
+            Down => 0b0100,
+            Left => 0b1000,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn direction_rotations() {
+        for d in Direction::ALL {
+            assert_eq!(d.rotate_right().rotate_left(), d);
+            assert_eq!(
+                d.rotate_right()
+                    .rotate_right()
+                    .rotate_right()
+                    .rotate_right(),
+                d
+            );
+        }
+        assert_eq!(Up.rotate_right(), Right);
+        assert_eq!(Up.rotate_left(), Left);
+        let all_bits = Direction::ALL.iter().fold(0, |bits, d| bits | d.bit());
+        assert_eq!(all_bits, 0b1111);
+    }
+
+    #[test]
+    fn direction_step() {
+        let grid = Grid::<u8>::new(3, 2, 0);
+        // interior-ish cell
+        assert_eq!(Up.step((1, 1), &grid), Some((1, 0)));
+        assert_eq!(Left.step((1, 1), &grid), Some((0, 1)));
+        assert_eq!(Right.step((1, 1), &grid), Some((2, 1)));
+        assert_eq!(Down.step((1, 0), &grid), Some((1, 1)));
+        // edges
+        assert_eq!(Down.step((1, 1), &grid), None);
+        assert_eq!(Up.step((2, 0), &grid), None);
+        assert_eq!(Right.step((2, 0), &grid), None);
+        assert_eq!(Left.step((0, 1), &grid), None);
+    }
+}
@@ -0,0 +1,279 @@
+//! Generic weighted graph (adjacency lists), to reuse
+//! the path-finding algorithms without a custom controller.
+
+use crate::dijkstra::DijkstraController;
+use crate::grid::Grid;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// A directed graph where each node maps to its list
+/// of (neighbor, distance).
+#[derive(Clone, Debug)]
+pub struct Graph<N> {
+    edges: HashMap<N, Vec<(N, usize)>>,
+}
+
+impl<N: Copy + Eq + Hash> Default for Graph<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N: Copy + Eq + Hash> Graph<N> {
+    pub fn new() -> Self {
+        Graph {
+            edges: HashMap::new(),
+        }
+    }
+
+    /// Add a node without any edge (if it doesn't exist yet)
+    pub fn add_node(&mut self, n: N) {
+        self.edges.entry(n).or_default();
+    }
+
+    /// Add a directed edge. Both nodes are created if needed.
+    pub fn add_edge(&mut self, from: N, to: N, distance: usize) {
+        self.add_node(to);
+        self.edges.entry(from).or_default().push((to, distance));
+    }
+
+    pub fn contains(&self, n: &N) -> bool {
+        self.edges.contains_key(n)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = &N> {
+        self.edges.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    /// The (neighbor, distance) list of a node, empty if the
+    /// node is unknown.
+    pub fn neighbors(&self, n: &N) -> &[(N, usize)] {
+        match self.edges.get(n) {
+            Some(v) => v,
+            None => &[],
+        }
+    }
+}
+
+impl Graph<(usize, usize)> {
+    /// Build the graph of all the cells of a grid equal to "passable",
+    /// connected to their passable 4-neighbors with a distance of 1.
+    /// Nodes are the (x,y) coordinates.
+    pub fn from_grid(grid: &Grid<bool>, passable: bool) -> Self {
+        let mut graph = Self::new();
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                if grid.get(x, y) != passable {
+                    continue;
+                }
+                graph.add_node((x, y));
+                let (sx, sy) = (x as isize, y as isize);
+                for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                    let (nx, ny) = (sx + dx, sy + dy);
+                    if grid.checked_get(nx, ny) == Some(passable) {
+                        graph.add_edge((x, y), (nx as usize, ny as usize), 1);
+                    }
+                }
+            }
+        }
+        graph
+    }
+}
+
+/// Iterative depth-first traversal of all the nodes reachable from
+/// "start" (included), calling "visit" exactly once on each of them,
+/// in pre-order. Neighbors are explored in their order of insertion.
+pub fn dfs<N: Copy + Eq + Hash>(graph: &Graph<N>, start: N, mut visit: impl FnMut(&N)) {
+    let mut visited = HashSet::<N>::new();
+    let mut stack = vec![start];
+
+    while let Some(node) = stack.pop() {
+        if !visited.insert(node) {
+            continue;
+        }
+        visit(&node);
+        // reversed so that the first neighbor is on top of the stack
+        for (next, _) in graph.neighbors(&node).iter().rev() {
+            if !visited.contains(next) {
+                stack.push(*next);
+            }
+        }
+    }
+}
+
+/// Order all the nodes of a directed graph so that each node comes
+/// before all the nodes it has an edge to (Kahn's algorithm).
+/// If the graph has a cycle, returns the nodes that could not be
+/// ordered (those on a cycle or reachable from one).
+pub fn topological_sort<N: Copy + Eq + Hash>(graph: &Graph<N>) -> Result<Vec<N>, Vec<N>> {
+    let mut in_degree = HashMap::<N, usize>::new();
+    for node in graph.nodes() {
+        in_degree.entry(*node).or_insert(0);
+        for (next, _) in graph.neighbors(node) {
+            *in_degree.entry(*next).or_insert(0) += 1;
+        }
+    }
+
+    let mut ready: Vec<N> = in_degree
+        .iter()
+        .filter(|(_, d)| **d == 0)
+        .map(|(n, _)| *n)
+        .collect();
+    let mut order = Vec::<N>::with_capacity(in_degree.len());
+
+    while let Some(node) = ready.pop() {
+        order.push(node);
+        for (next, _) in graph.neighbors(&node) {
+            let d = in_degree.get_mut(next).unwrap();
+            *d -= 1;
+            if *d == 0 {
+                ready.push(*next);
+            }
+        }
+    }
+
+    if order.len() == in_degree.len() {
+        Ok(order)
+    } else {
+        Err(in_degree
+            .into_iter()
+            .filter(|(_, d)| *d != 0)
+            .map(|(n, _)| n)
+            .collect())
+    }
+}
+
+/// A ready-made DijkstraController to search a path between
+/// two nodes of a Graph.
+/// After the search, "distances" holds the minimal distance
+/// of each visited node from the start.
+pub struct GraphSearch<'a, N> {
+    pub graph: &'a Graph<N>,
+    pub start: N,
+    pub target: N,
+    pub distances: HashMap<N, usize>,
+}
+
+impl<'a, N: Copy + Eq + Hash> GraphSearch<'a, N> {
+    pub fn new(graph: &'a Graph<N>, start: N, target: N) -> Self {
+        GraphSearch {
+            graph,
+            start,
+            target,
+            distances: HashMap::new(),
+        }
+    }
+}
+
+impl<N: Copy + Eq + Hash> DijkstraController for GraphSearch<'_, N> {
+    type Node = N;
+
+    fn get_starting_node(&self) -> Self::Node {
+        self.start
+    }
+
+    fn get_target_node(&self) -> Self::Node {
+        self.target
+    }
+
+    fn get_neighbors_distances(&self, node: &Self::Node) -> Vec<(Self::Node, usize)> {
+        self.graph.neighbors(node).to_vec()
+    }
+
+    fn mark_visited_distance(
+        &mut self,
+        node: Self::Node,
+        distance: usize,
+        _previous: Option<Self::Node>,
+    ) {
+        self.distances.insert(node, distance);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::dijkstra::dijkstra;
+    use crate::grid::GridBuilder;
+
+    #[test]
+    fn graph_dfs() {
+        // 0 -> 1 -> 3 -> 0 (cycle)
+        //   -> 2 -> 3
+        //             -> 4
+        // 5 -> 0 (unreachable from 0)
+        let mut graph = Graph::<usize>::new();
+        graph.add_edge(0, 1, 1);
+        graph.add_edge(0, 2, 1);
+        graph.add_edge(1, 3, 1);
+        graph.add_edge(2, 3, 1);
+        graph.add_edge(3, 0, 1);
+        graph.add_edge(3, 4, 1);
+        graph.add_edge(5, 0, 1);
+
+        let mut order = Vec::<usize>::new();
+        dfs(&graph, 0, |n| order.push(*n));
+        assert_eq!(order, vec![0, 1, 3, 4, 2]);
+
+        let mut order = Vec::<usize>::new();
+        dfs(&graph, 4, |n| order.push(*n));
+        assert_eq!(order, vec![4]);
+    }
+
+    #[test]
+    fn graph_topological_sort() {
+        // 0 -> 1 -> 3 -> 4
+        //   -> 2 -> 3
+        // 5 -> 2
+        let mut graph = Graph::<usize>::new();
+        graph.add_edge(0, 1, 1);
+        graph.add_edge(0, 2, 1);
+        graph.add_edge(1, 3, 1);
+        graph.add_edge(2, 3, 1);
+        graph.add_edge(3, 4, 1);
+        graph.add_edge(5, 2, 1);
+
+        let order = topological_sort(&graph).unwrap();
+        assert_eq!(order.len(), 6);
+        let pos = |n: usize| order.iter().position(|o| *o == n).unwrap();
+        for from in 0..6 {
+            for (to, _) in graph.neighbors(&from) {
+                assert!(pos(from) < pos(*to));
+            }
+        }
+
+        // 3 -> 1 closes the cycle 1 -> 3 -> 1, 4 is only downstream
+        graph.add_edge(3, 1, 1);
+        let mut remain = topological_sort(&graph).unwrap_err();
+        remain.sort();
+        assert_eq!(remain, vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn graph_from_grid_maze() {
+        let maze = ["..#....", ".##.##.", "....#..", "###.#.#", "......."];
+        let mut gb = GridBuilder::<bool>::new();
+        for row in maze {
+            gb.append_char_map(row, '#');
+        }
+        let walls = gb.to_grid();
+
+        let graph = Graph::from_grid(&walls, false);
+        assert_eq!(graph.len(), 24);
+        assert!(!graph.contains(&(2, 0)));
+        assert_eq!(graph.neighbors(&(0, 0)), &[((1, 0), 1), ((0, 1), 1)]);
+
+        let mut search = GraphSearch::new(&graph, (0, 0), (6, 0));
+        let d = dijkstra(&mut search, false);
+        assert_eq!(d, 10);
+        assert_eq!(search.distances.get(&(3, 2)), Some(&5));
+    }
+}
@@ -1,7 +1,11 @@
 //! "Grid" storage (2D array).
 
 use crate::colors;
+use crate::direction::Direction;
 use std::boxed::Box;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{self, Write};
+use std::ops::{Add, Index, IndexMut, Mul};
 
 // A custom 2D array more friendly than a Vec<Vec<T>>
 #[derive(Clone)]
@@ -11,6 +15,53 @@ pub struct Grid<T> {
     s: Box<[T]>,
 }
 
+/// Orientation changes of a grid.
+/// Rotations are clockwise; FlipH mirrors left-right and
+/// FlipV mirrors top-bottom.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transform {
+    Rotate0,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    FlipH,
+    FlipV,
+}
+
+/// How pad_with() fills the margin around a grid.
+/// Reflect mirrors the cells next to the edge (the edge itself being
+/// repeated once), Wrap copies the cells of the opposite edge.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PadMode<T> {
+    Constant(T),
+    Reflect,
+    Wrap,
+}
+
+/// Details of a single region, as returned by flood_region_detailed().
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionInfo {
+    /// All the cells of the region, in flood order
+    pub cells: Vec<(usize, usize)>,
+    pub area: usize,
+    /// Number of edges between the region and other cells or the outside
+    pub perimeter: usize,
+    /// Cells of the region with at least one edge on the perimeter
+    pub border_cells: Vec<(usize, usize)>,
+}
+
+/// (dx,dy) of the 8 neighbors of a cell, clockwise starting from the right.
+const NEIGHBORS8_OFFSETS: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
 impl<T: std::clone::Clone> Grid<T> {
     /// Allocate the low-level array for this grid with a default value
     pub fn new(width: usize, height: usize, t0: T) -> Self {
@@ -21,6 +72,21 @@ impl<T: std::clone::Clone> Grid<T> {
         }
     }
 
+    /// Create a grid of (interior_w+2) x (interior_h+2) cells, where
+    /// the outer frame is filled with "border" and the rest with
+    /// "interior". The interior starts at (1,1).
+    pub fn new_with_border(interior_w: usize, interior_h: usize, interior: T, border: T) -> Self {
+        let mut grid = Self::new(interior_w + 2, interior_h + 2, interior);
+        for y in 0..grid.height {
+            for x in 0..grid.width {
+                if grid.is_border(x, y) {
+                    grid.set(x, y, border.clone());
+                }
+            }
+        }
+        grid
+    }
+
     /// Convert a double-vector into a grid.
     /// Internal vectors are supposed to all be the same length;
     /// the first one is taken as the "width" of the final grid,
@@ -59,12 +125,79 @@ impl<T: std::clone::Clone> Grid<T> {
         }
     }
 
+    /// True if (x,y) is on the outer frame of the grid
+    /// (first or last row or column).
+    pub fn is_border(&self, x: usize, y: usize) -> bool {
+        x == 0 || y == 0 || x + 1 == self.width || y + 1 == self.height
+    }
+
+    /// Iterate over the coordinates of the outer frame, each cell once,
+    /// clockwise from the top-left corner.
+    pub fn border_coords(&self) -> impl Iterator<Item = (usize, usize)> {
+        let (w, h) = (self.width, self.height);
+        let right = if w > 0 { 1..h } else { 0..0 };
+        let bottom = if h > 1 { 0..w.saturating_sub(1) } else { 0..0 };
+        let left = if w > 1 { 1..h.saturating_sub(1) } else { 0..0 };
+
+        (0..w)
+            .map(|x| (x, 0))
+            .chain(right.map(move |y| (w - 1, y)))
+            .chain(bottom.rev().map(move |x| (x, h - 1)))
+            .chain(left.rev().map(|y| (0, y)))
+    }
+
     // return a slice of size "width" of all elements of row Y
     pub fn get_row_slice(&self, y: usize) -> &[T] {
         if y >= self.height {
             panic!("array row {y} out of bounds");
         }
-        &self.s[y * self.width..y * self.width + self.height]
+        &self.s[y * self.width..y * self.width + self.width]
+    }
+
+    /// Return a mutable slice of all the elements of row y,
+    /// for in-place row editing.
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        if y >= self.height {
+            panic!("array row {y} out of bounds");
+        }
+        &mut self.s[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Iterate over all the sliding windows of size n within row y
+    /// (no window if n is larger than the width).
+    /// Panics if n is 0.
+    pub fn row_windows(&self, y: usize, n: usize) -> impl Iterator<Item = &[T]> {
+        if y >= self.height {
+            panic!("array row {y} out of bounds");
+        }
+        if n == 0 {
+            panic!("row window size must be at least 1");
+        }
+        self.s[y * self.width..(y + 1) * self.width].windows(n)
+    }
+
+    /// Iterate over the elements of column x, top to bottom.
+    pub fn iter_column(&self, x: usize) -> impl Iterator<Item = &T> {
+        if x >= self.width {
+            panic!("array column {x} out of bounds");
+        }
+        self.s[x..].iter().step_by(self.width)
+    }
+
+    /// Copy of all the elements of column x, top to bottom.
+    pub fn column_to_vec(&self, x: usize) -> Vec<T> {
+        self.iter_column(x).cloned().collect()
+    }
+
+    /// Iterate over all the cells as (x, y, value) in reverse
+    /// reading order, from bottom-right to top-left.
+    pub fn iter_coords_rev(&self) -> impl Iterator<Item = (usize, usize, &T)> {
+        let width = self.width;
+        self.s
+            .iter()
+            .enumerate()
+            .rev()
+            .map(move |(i, v)| (i % width, i / width, v))
     }
 
     pub fn get_mut(&mut self, x: isize, y: isize) -> Option<&mut T> {
@@ -84,10 +217,358 @@ impl<T: std::clone::Clone> Grid<T> {
         }
     }
 
+    /// Return the 4 orthogonal neighbor coordinates of (x,y), in the
+    /// order right, left, down, up, without any bounds filtering:
+    /// callers can use checked_get() directly on them.
+    pub fn neighbors4_signed(&self, x: isize, y: isize) -> Vec<(isize, isize)> {
+        vec![(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)]
+    }
+
+    /// Return the in-bounds orthogonal neighbor coordinates of (x,y),
+    /// in the order right, left, down, up.
+    pub fn neighbors4(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        self.neighbors_custom(x, y, &[(1, 0), (-1, 0), (0, 1), (0, -1)])
+    }
+
+    /// Return the in-bounds neighbor coordinates of (x,y) including
+    /// diagonals, clockwise starting from the right.
+    pub fn neighbors8(&self, x: usize, y: usize) -> Vec<(usize, usize)> {
+        self.neighbors_custom(x, y, &NEIGHBORS8_OFFSETS)
+    }
+
+    /// Number of in-bounds orthogonal neighbors of (x,y) for which
+    /// pred holds.
+    pub fn matching_neighbors4(&self, x: usize, y: usize, pred: impl Fn(&T) -> bool) -> usize {
+        self.neighbors4(x, y)
+            .into_iter()
+            .filter(|(nx, ny)| pred(&self[(*nx, *ny)]))
+            .count()
+    }
+
+    /// Same as matching_neighbors4() including the diagonals.
+    pub fn matching_neighbors8(&self, x: usize, y: usize, pred: impl Fn(&T) -> bool) -> usize {
+        self.neighbors8(x, y)
+            .into_iter()
+            .filter(|(nx, ny)| pred(&self[(*nx, *ny)]))
+            .count()
+    }
+
+    /// Values of the four diagonal neighbors of (x,y), in the order
+    /// NW, NE, SW, SE; None for those out of the grid.
+    pub fn diagonal_neighbors(&self, x: usize, y: usize) -> [Option<T>; 4] {
+        let (x, y) = (x as isize, y as isize);
+        [(-1, -1), (1, -1), (-1, 1), (1, 1)].map(|(dx, dy)| self.checked_get(x + dx, y + dy))
+    }
+
+    /// One step of a cellular automaton: each cell of the new grid is
+    /// rule(cell, neighbors), with the 8 neighbors in the same order as
+    /// neighbors8() and None for those out of the grid.
+    pub fn step<U: Clone>(&self, rule: impl Fn(&T, &[Option<T>; 8]) -> U) -> Grid<U> {
+        let mut s = Vec::<U>::with_capacity(self.s.len());
+        for y in 0..self.height as isize {
+            for x in 0..self.width as isize {
+                let neighbors = NEIGHBORS8_OFFSETS.map(|(dx, dy)| self.checked_get(x + dx, y + dy));
+                s.push(rule(
+                    &self.s[x as usize + y as usize * self.width],
+                    &neighbors,
+                ));
+            }
+        }
+        Grid::<U> {
+            width: self.width,
+            height: self.height,
+            s: s.into_boxed_slice(),
+        }
+    }
+
+    /// Return the in-bounds coordinates of the cells at each of the
+    /// given (dx,dy) offsets from (x,y), in the order of the offsets.
+    pub fn neighbors_custom(
+        &self,
+        x: usize,
+        y: usize,
+        offsets: &[(isize, isize)],
+    ) -> Vec<(usize, usize)> {
+        offsets
+            .iter()
+            .map(|(dx, dy)| (x as isize + dx, y as isize + dy))
+            .filter(|(nx, ny)| {
+                *nx >= 0 && *ny >= 0 && (*nx as usize) < self.width && (*ny as usize) < self.height
+            })
+            .map(|(nx, ny)| (nx as usize, ny as usize))
+            .collect()
+    }
+
+    /// Return all the cells reachable from start by moving
+    /// to 4-connected neighbors, in breadth-first order (start first).
+    /// continue_pred(current_value, neighbor_value) decides if the
+    /// fill can expand from the current cell into the neighbor.
+    pub fn flood_fill_while(
+        &self,
+        start: (usize, usize),
+        continue_pred: impl Fn(&T, &T) -> bool,
+    ) -> Vec<(usize, usize)> {
+        let mut visited = Grid::<bool>::new(self.width, self.height, false);
+        let mut queue = VecDeque::<(usize, usize)>::new();
+        let mut filled = Vec::new();
+
+        visited.set(start.0, start.1, true);
+        queue.push_back(start);
+
+        while let Some((x, y)) = queue.pop_front() {
+            filled.push((x, y));
+            let current = self.get(x, y);
+            for (nx, ny) in self.neighbors4(x, y) {
+                if !visited.get(nx, ny) && continue_pred(&current, &self.get(nx, ny)) {
+                    visited.set(nx, ny, true);
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+
+        filled
+    }
+
+    /// Return a new grid of the same dimension, where each
+    /// cell is f() applied to the cell of this grid.
+    pub fn map<U: Clone>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid::<U> {
+            width: self.width,
+            height: self.height,
+            s: self.s.iter().map(f).collect(),
+        }
+    }
+
+    /// Count the cells grouped by the key computed for each of them.
+    pub fn count_by<K: Eq + std::hash::Hash>(&self, key: impl Fn(&T) -> K) -> HashMap<K, usize> {
+        let mut counts = HashMap::new();
+        for v in self.s.iter() {
+            *counts.entry(key(v)).or_default() += 1;
+        }
+        counts
+    }
+
+    /// Coordinates and value of the cell with the largest key,
+    /// the first one in reading order in case of a tie.
+    /// None if the grid is empty.
+    pub fn max_by_key<K: Ord>(&self, key: impl Fn(&T) -> K) -> Option<((usize, usize), T)> {
+        self.best_by_key(key, |k, best| k > best)
+    }
+
+    /// Coordinates and value of the cell with the smallest key,
+    /// the first one in reading order in case of a tie.
+    /// None if the grid is empty.
+    pub fn min_by_key<K: Ord>(&self, key: impl Fn(&T) -> K) -> Option<((usize, usize), T)> {
+        self.best_by_key(key, |k, best| k < best)
+    }
+
+    fn best_by_key<K: Ord>(
+        &self,
+        key: impl Fn(&T) -> K,
+        better: impl Fn(&K, &K) -> bool,
+    ) -> Option<((usize, usize), T)> {
+        let mut best: Option<(usize, K)> = None;
+        for (i, v) in self.s.iter().enumerate() {
+            let k = key(v);
+            if best.as_ref().is_none_or(|(_, b)| better(&k, b)) {
+                best = Some((i, k));
+            }
+        }
+        best.map(|(i, _)| ((i % self.width, i / self.width), self.s[i].clone()))
+    }
+
+    /// Count the pairs of horizontally adjacent cells, (x,y) and
+    /// (x+1,y), for which pred(left, right) holds.
+    pub fn horizontal_transitions(&self, pred: impl Fn(&T, &T) -> bool) -> usize {
+        self.s
+            .chunks_exact(self.width.max(1))
+            .flat_map(|row| row.windows(2))
+            .filter(|w| pred(&w[0], &w[1]))
+            .count()
+    }
+
+    /// Count the pairs of vertically adjacent cells, (x,y) and
+    /// (x,y+1), for which pred(top, bottom) holds.
+    pub fn vertical_transitions(&self, pred: impl Fn(&T, &T) -> bool) -> usize {
+        self.s
+            .iter()
+            .zip(self.s.iter().skip(self.width))
+            .filter(|(top, bottom)| pred(top, bottom))
+            .count()
+    }
+
+    /// Smallest rectangle containing all the cells for which pred
+    /// holds, as (min_x, min_y, max_x, max_y) inclusive.
+    /// None if no cell matches.
+    pub fn bounding_box(&self, pred: impl Fn(&T) -> bool) -> Option<(usize, usize, usize, usize)> {
+        let mut bbox: Option<(usize, usize, usize, usize)> = None;
+        for (i, v) in self.s.iter().enumerate() {
+            if !pred(v) {
+                continue;
+            }
+            let (x, y) = (i % self.width, i / self.width);
+            bbox = Some(match bbox {
+                None => (x, y, x, y),
+                Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+            });
+        }
+        bbox
+    }
+
     /// Fill the grid by replacing all cells by a single value
     pub fn fill(&mut self, t: T) {
         self.s.fill(t);
     }
+
+    /// Fill the grid with the value computed by f(x,y) for each cell.
+    /// Stops at the first error and returns it, leaving the grid unchanged.
+    pub fn try_fill_with<E>(&mut self, f: impl Fn(usize, usize) -> Result<T, E>) -> Result<(), E> {
+        let mut filled = Vec::with_capacity(self.s.len());
+        for y in 0..self.height {
+            for x in 0..self.width {
+                filled.push(f(x, y)?);
+            }
+        }
+        self.s = filled.into_boxed_slice();
+        Ok(())
+    }
+
+    /// Return a copy of the grid with its orientation transformed.
+    /// Width and height are swapped by the 90° and 270° rotations.
+    pub fn transformed(&self, transform: Transform) -> Grid<T> {
+        let (w, h) = (self.width, self.height);
+        let (new_w, new_h) = match transform {
+            Transform::Rotate90 | Transform::Rotate270 => (h, w),
+            _ => (w, h),
+        };
+        let mut s = Vec::<T>::with_capacity(w * h);
+        for y in 0..new_h {
+            for x in 0..new_w {
+                let (sx, sy) = match transform {
+                    Transform::Rotate0 => (x, y),
+                    Transform::Rotate90 => (y, h - 1 - x),
+                    Transform::Rotate180 => (w - 1 - x, h - 1 - y),
+                    Transform::Rotate270 => (w - 1 - y, x),
+                    Transform::FlipH => (w - 1 - x, y),
+                    Transform::FlipV => (x, h - 1 - y),
+                };
+                s.push(self.get(sx, sy));
+            }
+        }
+        Grid::<T> {
+            width: new_w,
+            height: new_h,
+            s: s.into_boxed_slice(),
+        }
+    }
+
+    /// Copy all the cells of "src" into this grid, with the top-left
+    /// corner of "src" at (x,y).
+    /// Cells falling outside of this grid are silently clipped.
+    pub fn blit(&mut self, src: &Grid<T>, x: usize, y: usize) {
+        if x >= self.width {
+            return;
+        }
+        for sy in 0..src.height {
+            if y + sy >= self.height {
+                break;
+            }
+            let len = src.width.min(self.width.saturating_sub(x));
+            let start = x + (y + sy) * self.width;
+            let src_start = sy * src.width;
+            self.s[start..start + len].clone_from_slice(&src.s[src_start..src_start + len]);
+        }
+    }
+
+    /// Return a copy of this grid with new dimensions, keeping the
+    /// overlapping top-left content and filling the new cells with "fill".
+    pub fn resize(&self, new_w: usize, new_h: usize, fill: T) -> Grid<T> {
+        let mut resized = Grid::new(new_w, new_h, fill);
+        resized.blit(self, 0, 0);
+        resized
+    }
+
+    /// Return a copy of this grid translated by (dx,dy): cells moved
+    /// out of the grid are lost and the vacated ones are set to "fill".
+    pub fn shift(&self, dx: isize, dy: isize, fill: T) -> Grid<T> {
+        let mut shifted = Grid::new(self.width, self.height, fill);
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                if let Some(cell) = shifted.get_mut(nx, ny) {
+                    *cell = self.s[x + y * self.width].clone();
+                }
+            }
+        }
+        shifted
+    }
+
+    /// Return a copy of this grid surrounded by "margin" cells on every
+    /// side, filled according to "mode".
+    /// Reflect and Wrap repeat the pattern if the margin is larger
+    /// than the grid (which must then not be empty).
+    pub fn pad_with(&self, margin: usize, mode: PadMode<T>) -> Grid<T> {
+        let pad_index = |i: isize, len: usize| -> Option<usize> {
+            let len = len as isize;
+            match mode {
+                PadMode::Constant(_) => (0..len).contains(&i).then_some(i as usize),
+                PadMode::Reflect => {
+                    let m = i.rem_euclid(2 * len);
+                    Some(if m < len { m } else { 2 * len - 1 - m } as usize)
+                }
+                PadMode::Wrap => Some(i.rem_euclid(len) as usize),
+            }
+        };
+        let (width, height) = (self.width + 2 * margin, self.height + 2 * margin);
+        let mut s = Vec::with_capacity(width * height);
+        for py in 0..height {
+            for px in 0..width {
+                let sx = pad_index(px as isize - margin as isize, self.width);
+                let sy = pad_index(py as isize - margin as isize, self.height);
+                s.push(match (sx, sy, &mode) {
+                    (Some(sx), Some(sy), _) => self.s[sx + sy * self.width].clone(),
+                    (_, _, PadMode::Constant(t)) => t.clone(),
+                    _ => unreachable!(),
+                });
+            }
+        }
+        Grid {
+            width,
+            height,
+            s: s.into_boxed_slice(),
+        }
+    }
+
+    /// Same as blit() with "src" first rotated or flipped.
+    pub fn blit_transformed(&mut self, src: &Grid<T>, x: usize, y: usize, transform: Transform) {
+        self.blit(&src.transformed(transform), x, y);
+    }
+
+    /// Cyclic shift of all the values of row Y, by "by" cells
+    /// to the right (or to the left if negative).
+    /// Cells moved past the end wrap around to the other side.
+    pub fn rotate_row(&mut self, y: usize, by: isize) {
+        if y >= self.height {
+            panic!("array row {y} out of bounds");
+        }
+        let by = by.rem_euclid(self.width as isize) as usize;
+        self.s[y * self.width..(y + 1) * self.width].rotate_right(by);
+    }
+
+    /// Cyclic shift of all the values of column X, by "by" cells
+    /// downward (or upward if negative).
+    /// Cells moved past the end wrap around to the other side.
+    pub fn rotate_column(&mut self, x: usize, by: isize) {
+        if x >= self.width {
+            panic!("array column {x} out of bounds");
+        }
+        let by = by.rem_euclid(self.height as isize) as usize;
+        let mut column: Vec<T> = (0..self.height).map(|y| self.get(x, y)).collect();
+        column.rotate_right(by);
+        for (y, t) in column.into_iter().enumerate() {
+            self.set(x, y, t);
+        }
+    }
 }
 
 impl<T: PartialEq + std::clone::Clone> Grid<T> {
@@ -102,19 +583,191 @@ impl<T: PartialEq + std::clone::Clone> Grid<T> {
 
         false
     }
+
+    /// Count the unordered pairs of orthogonally adjacent cells
+    /// that have equal values.
+    pub fn adjacent_equal_pairs(&self) -> usize {
+        let mut count = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let v = &self.s[x + y * self.width];
+                if x + 1 < self.width && *v == self.s[x + 1 + y * self.width] {
+                    count += 1;
+                }
+                if y + 1 < self.height && *v == self.s[x + (y + 1) * self.width] {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// Coordinates of the cells where this grid and "other" have
+    /// different values, in reading order.
+    /// Both grids must have the same dimensions.
+    pub fn diff(&self, other: &Grid<T>) -> Vec<(usize, usize)> {
+        assert_eq!((self.width, self.height), (other.width, other.height));
+        (0..self.s.len())
+            .filter(|i| self.s[*i] != other.s[*i])
+            .map(|i| (i % self.width, i / self.width))
+            .collect()
+    }
+
+    /// Indices of the rows that differ from the "reference" row in at
+    /// most max_diff columns (the reference row itself included).
+    /// This is only a heuristic to spot near-duplicate rows, like
+    /// groups of nodes in an adjacency matrix (day 23): rows being
+    /// close doesn't guarantee anything about the structure.
+    pub fn rows_within_hamming(&self, reference: usize, max_diff: usize) -> Vec<usize> {
+        let ref_row = self.get_row_slice(reference);
+        (0..self.height)
+            .filter(|y| crate::util::hamming_distance(ref_row, self.get_row_slice(*y)) <= max_diff)
+            .collect()
+    }
+
+    /// Label each 4-connected region of equal values with a unique id,
+    /// from 0 in reading order of the first cell of each region.
+    /// Returns the grid of labels and the number of regions.
+    pub fn label_regions(&self) -> (Grid<usize>, usize) {
+        let mut labels = Grid::<usize>::new(self.width, self.height, usize::MAX);
+        let mut count = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if labels.get(x, y) != usize::MAX {
+                    continue;
+                }
+                for (rx, ry) in self.flood_fill_while((x, y), |c, n| c == n) {
+                    labels.set(rx, ry, count);
+                }
+                count += 1;
+            }
+        }
+        (labels, count)
+    }
+
+    /// Flood the 4-connected region of equal values containing start,
+    /// and return its cells, area, perimeter and border cells.
+    pub fn flood_region_detailed(&self, start: (usize, usize)) -> RegionInfo {
+        let cells = self.flood_fill_while(start, |c, n| c == n);
+        let value = self.get(start.0, start.1);
+        let mut perimeter = 0;
+        let mut border_cells = Vec::new();
+        for (x, y) in cells.iter() {
+            let edges = self
+                .neighbors4_signed(*x as isize, *y as isize)
+                .into_iter()
+                .filter(|(nx, ny)| self.checked_get(*nx, *ny).as_ref() != Some(&value))
+                .count();
+            if edges > 0 {
+                perimeter += edges;
+                border_cells.push((*x, *y));
+            }
+        }
+
+        RegionInfo {
+            area: cells.len(),
+            cells,
+            perimeter,
+            border_cells,
+        }
+    }
+
+    /// Replace each cell of the 4-connected region of equal values
+    /// containing start by f(cell).
+    pub fn map_region(&mut self, start: (usize, usize), f: impl Fn(&T) -> T) {
+        for (x, y) in self.flood_fill_while(start, |c, n| c == n) {
+            let v = f(&self.s[x + y * self.width]);
+            self.s[x + y * self.width] = v;
+        }
+    }
+
+    /// Map each region size to the number of regions of that size
+    /// (regions as in label_regions()).
+    pub fn region_size_histogram(&self) -> HashMap<usize, usize> {
+        let (labels, count) = self.label_regions();
+        let mut sizes = vec![0; count];
+        for label in labels.s.iter() {
+            sizes[*label] += 1;
+        }
+
+        let mut histogram = HashMap::new();
+        for size in sizes {
+            *histogram.entry(size).or_default() += 1;
+        }
+        histogram
+    }
+}
+
+/// Return all the 4-connected coordinates, from start, holding the
+/// same value as the start cell (in breadth-first order, start first).
+/// Same as flood_fill_while() with an equality predicate.
+pub fn flood_fill<T: Clone + PartialEq>(
+    grid: &Grid<T>,
+    start: (usize, usize),
+) -> Vec<(usize, usize)> {
+    let value = grid.get(start.0, start.1);
+    grid.flood_fill_while(start, |_, n| *n == value)
+}
+
+/// Number of 4-connected regions of equal values, for callers
+/// that don't need the labels of label_regions().
+pub fn region_count<T: Clone + PartialEq>(grid: &Grid<T>) -> u32 {
+    grid.label_regions().1 as u32
+}
+
+impl<T: Add<Output = T> + Default + Copy> Grid<T> {
+    /// Sum of all the cells, T::default() for an empty grid.
+    pub fn sum(&self) -> T {
+        self.s.iter().fold(T::default(), |acc, v| acc + *v)
+    }
+}
+
+impl<T: Mul<Output = T> + From<u8> + Copy> Grid<T> {
+    /// Product of all the cells, 1 for an empty grid.
+    pub fn product(&self) -> T {
+        self.s.iter().fold(T::from(1), |acc, v| acc * *v)
+    }
+}
+
+/// Direct access to a cell by reference with grid[(x, y)]
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        if x >= self.width || y >= self.height {
+            panic!("array access {},{} out of bounds", x, y);
+        }
+        &self.s[x + y * self.width]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        if x >= self.width || y >= self.height {
+            panic!("array access {},{} out of bounds", x, y);
+        }
+        &mut self.s[x + y * self.width]
+    }
 }
 
 impl<T: std::clone::Clone + std::fmt::Display> Grid<T> {
     /// Pretty-print the array with default Display trait
     pub fn pretty_print(&self) {
-        eprintln!("[{},{}] = ", self.width, self.height);
+        self.to_writer(&mut io::stderr().lock()).unwrap();
+    }
+
+    /// Same as pretty_print() but to any writer
+    /// (stdout, a file, a memory buffer...)
+    pub fn to_writer(&self, w: &mut impl Write) -> io::Result<()> {
+        writeln!(w, "[{},{}] = ", self.width, self.height)?;
         for y in 0..self.height {
-            eprint!("[ ");
+            write!(w, "[ ")?;
             for x in 0..self.width {
-                eprint!("{} ", self.get(x, y));
+                write!(w, "{} ", self.get(x, y))?;
             }
-            eprintln!("]");
+            writeln!(w, "]")?;
         }
+        Ok(())
     }
 }
 
@@ -122,22 +775,44 @@ impl<T: std::clone::Clone> Grid<T> {
     /// Pretty-print the array with any user-supplied function to convert
     /// between the type and a single char (not simply a "Display" trait)
     pub fn pretty_print_lambda_char(&self, f: &dyn Fn(T) -> char) {
-        eprintln!("[{},{}] = ", self.width, self.height);
-        for y in 0..self.height {
-            let s: String = (0..self.width).map(|x| f(self.get(x, y))).collect();
-            eprintln!("[{}] ", s);
-        }
+        self.to_writer_lambda(&mut io::stderr().lock(), &|t| f(t).to_string())
+            .unwrap();
     }
 
     /// Pretty-print the array with any user-supplied function to convert
     /// between the type and any string (should all be the same size for
     /// alignment)
     pub fn pretty_print_lambda(&self, f: &dyn Fn(T) -> String) {
-        eprintln!("[{},{}] = ", self.width, self.height);
+        self.to_writer_lambda(&mut io::stderr().lock(), f).unwrap();
+    }
+
+    /// Same as pretty_print_lambda() but to any writer
+    pub fn to_writer_lambda(&self, w: &mut impl Write, f: &dyn Fn(T) -> String) -> io::Result<()> {
+        writeln!(w, "[{},{}] = ", self.width, self.height)?;
         for y in 0..self.height {
             let s: String = (0..self.width).map(|x| f(self.get(x, y))).collect();
-            eprintln!("[{}] ", s);
+            writeln!(w, "[{}] ", s)?;
         }
+        Ok(())
+    }
+
+    /// Write the grid as a binary PPM (P6) image of width x height
+    /// pixels, with the (r,g,b) color of each cell given by "color".
+    pub fn write_ppm<W: Write>(
+        &self,
+        w: &mut W,
+        color: impl Fn(&T) -> (u8, u8, u8),
+    ) -> io::Result<()> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        let pixels: Vec<u8> = self
+            .s
+            .iter()
+            .flat_map(|v| {
+                let (r, g, b) = color(v);
+                [r, g, b]
+            })
+            .collect();
+        w.write_all(&pixels)
     }
 
     /// Pretty-print the array with any user-supplied function,
@@ -344,54 +1019,392 @@ impl Grid<bool> {
     }
 }
 
-/// A builder to construct a Grid by parsing lines
-/// one by one (without knowing the final size)
-/// (Note: this is not strictly the Builder Pattern, needs a better name ?)
-#[derive(Clone)]
-pub struct GridBuilder<T> {
-    width: usize,
-    height: usize,
-    s: Vec<T>,
-}
+impl Grid<usize> {
+    /// Pretty-print a map of values (typically distances) with one
+    /// character per cell: the last digit of the value on a background
+    /// color from a cold to hot scale between min and max.
+    /// Values out of [min,max] (walls, unreachable cells...) are
+    /// printed as an uncolored '.'.
+    /// Colors are disabled if NO_COLOR is set.
+    pub fn pretty_print_heat(&self, min: usize, max: usize) {
+        self.to_writer_heat(&mut io::stderr().lock(), min, max, colors::use_colors())
+            .unwrap();
+    }
 
-impl<T: std::clone::Clone> Default for GridBuilder<T> {
-    fn default() -> Self {
-        Self::new()
+    /// Same as pretty_print_heat() but to any writer,
+    /// with or without colors.
+    pub fn to_writer_heat(
+        &self,
+        w: &mut impl Write,
+        min: usize,
+        max: usize,
+        use_colors: bool,
+    ) -> io::Result<()> {
+        self.to_writer_lambda(w, &|v| {
+            if v < min || v > max {
+                ".".to_string()
+            } else if use_colors {
+                format!(
+                    "{}{}{}",
+                    colors::heat_color(v, min, max),
+                    v % 10,
+                    colors::ANSI_RESET
+                )
+            } else {
+                format!("{}", v % 10)
+            }
+        })
     }
-}
 
-impl<T: std::clone::Clone> GridBuilder<T> {
-    /// Create an initially empty builder.
-    pub fn new() -> Self {
-        GridBuilder {
-            width: 0,
-            height: 0,
-            s: Vec::<T>::new(),
-        }
+    /// Count the distinct paths from start to any cell where is_end()
+    /// is true, moving to 4-connected neighbors when
+    /// is_edge(current_value, neighbor_value) is true.
+    /// Edges must not create cycles (for example strictly increasing
+    /// values), the count of each cell is memoized.
+    /// A path stops at the first end cell reached.
+    pub fn count_paths_monotone(
+        &self,
+        start: (usize, usize),
+        is_edge: impl Fn(&usize, &usize) -> bool,
+        is_end: impl Fn(&usize) -> bool,
+    ) -> usize {
+        let mut memo = Grid::<Option<usize>>::new(self.width, self.height, None);
+        self.count_paths_internal(start, &is_edge, &is_end, &mut memo)
     }
 
-    /// Add a new row at the end of the builder.
-    /// When it's the first time, this row defines the width
-    /// of the grid. All other rows must have the same width
-    /// else a panic is emitted.
-    pub fn append_line(&mut self, line: &[T]) {
-        if self.height == 0 {
-            self.width = line.len();
-        } else if self.width != line.len() {
-            panic!(
-                "Row of len {} appended to GridBuilder of width {}",
-                line.len(),
-                self.width
-            );
+    fn count_paths_internal(
+        &self,
+        (x, y): (usize, usize),
+        is_edge: &impl Fn(&usize, &usize) -> bool,
+        is_end: &impl Fn(&usize) -> bool,
+        memo: &mut Grid<Option<usize>>,
+    ) -> usize {
+        if let Some(count) = memo.get(x, y) {
+            return count;
         }
 
-        self.height += 1;
-        self.s.extend_from_slice(line);
+        let value = self.get(x, y);
+        let count = if is_end(&value) {
+            1
+        } else {
+            self.neighbors4(x, y)
+                .into_iter()
+                .filter(|(nx, ny)| is_edge(&value, &self.get(*nx, *ny)))
+                .map(|n| self.count_paths_internal(n, is_edge, is_end, memo))
+                .sum()
+        };
+        memo.set(x, y, Some(count));
+        count
     }
+}
 
-    /// Convert into the final Grid when nothing else needs appending.
-    pub fn to_grid(self) -> Grid<T> {
-        if self.s.is_empty() {
+impl Grid<u16> {
+    /// Convert into a boolean map where cells with a value
+    /// lower or equal to t are true.
+    pub fn threshold(&self, t: u16) -> Grid<bool> {
+        self.map(|v| *v <= t)
+    }
+}
+
+impl Grid<char> {
+    /// Convert a char grid into a boolean map where only
+    /// "true_char" is true (same as GridBuilder::append_char_map
+    /// for a grid that is already parsed).
+    pub fn to_bool(&self, true_char: char) -> Grid<bool> {
+        self.map(|c| *c == true_char)
+    }
+}
+
+impl Grid<bool> {
+    /// Return the coordinates of all the `false` cells that are
+    /// not connected to the border of the grid through other
+    /// 4-connected `false` cells (interior pockets enclosed by `true`).
+    pub fn enclosed_cells(&self) -> Vec<(usize, usize)> {
+        // Flood the outside from all the border cells,
+        // everything false and not reached is enclosed.
+        let mut outside = Grid::<bool>::new(self.width, self.height, false);
+        let mut queue = VecDeque::<(usize, usize)>::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let border = x == 0 || y == 0 || x == self.width - 1 || y == self.height - 1;
+                if border && !self.get(x, y) {
+                    outside.set(x, y, true);
+                    queue.push_back((x, y));
+                }
+            }
+        }
+
+        while let Some((x, y)) = queue.pop_front() {
+            for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+                let (nx, ny) = (x as isize + dx, y as isize + dy);
+                if self.checked_get(nx, ny) == Some(false)
+                    && outside.checked_get(nx, ny) == Some(false)
+                {
+                    outside.set(nx as usize, ny as usize, true);
+                    queue.push_back((nx as usize, ny as usize));
+                }
+            }
+        }
+
+        let mut enclosed = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !self.get(x, y) && !outside.get(x, y) {
+                    enclosed.push((x, y));
+                }
+            }
+        }
+        enclosed
+    }
+
+    /// Follow a one-wide track of passable (false) cells from start
+    /// to end, and return the ordered cells of the track (both ends included).
+    /// Expects each cell of the track to have a single next cell
+    /// besides the one it comes from. Panics on a dead-end.
+    pub fn follow_single_path(
+        &self,
+        start: (usize, usize),
+        end: (usize, usize),
+    ) -> Vec<(usize, usize)> {
+        let mut path = vec![start];
+        let mut previous: Option<(usize, usize)> = None;
+        let mut pos = start;
+        while pos != end {
+            let next = self
+                .neighbors4(pos.0, pos.1)
+                .into_iter()
+                .find(|n| !self.get(n.0, n.1) && Some(*n) != previous);
+            match next {
+                Some(n) => {
+                    previous = Some(pos);
+                    pos = n;
+                    path.push(pos);
+                }
+                None => panic!("dead-end at {},{} before reaching the end", pos.0, pos.1),
+            }
+        }
+        path
+    }
+
+    /// Return the passable (false) cell with the largest distance from
+    /// start, moving through 4-connected passable cells, with that distance.
+    /// Returns None if start itself is not passable.
+    pub fn farthest_reachable(&self, start: (usize, usize)) -> Option<((usize, usize), usize)> {
+        if self.get(start.0, start.1) {
+            return None;
+        }
+
+        let mut distances = Grid::<usize>::new(self.width, self.height, usize::MAX);
+        let mut queue = VecDeque::<(usize, usize)>::new();
+        let mut farthest = (start, 0);
+        distances.set(start.0, start.1, 0);
+        queue.push_back(start);
+
+        while let Some((x, y)) = queue.pop_front() {
+            let d = distances.get(x, y);
+            if d > farthest.1 {
+                farthest = ((x, y), d);
+            }
+            for (nx, ny) in self.neighbors4(x, y) {
+                if !self.get(nx, ny) && distances.get(nx, ny) == usize::MAX {
+                    distances.set(nx, ny, d + 1);
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+
+        Some(farthest)
+    }
+
+    /// Coordinates of all the true cells, in reading order.
+    pub fn true_coords(&self) -> Vec<(usize, usize)> {
+        self.s
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| **v)
+            .map(|(i, _)| (i % self.width, i / self.width))
+            .collect()
+    }
+
+    /// Same as true_coords() but as a set, for fast membership
+    /// tests or set operations between grids.
+    pub fn true_set(&self) -> HashSet<(usize, usize)> {
+        self.true_coords().into_iter().collect()
+    }
+
+    /// Set to true every cell that is true in other (logical OR in place).
+    /// The two grids must have the same dimension.
+    pub fn overlay_in_place(&mut self, other: &Grid<bool>) {
+        assert_eq!((self.width, self.height), (other.width, other.height));
+        for (c, o) in self.s.iter_mut().zip(other.s.iter()) {
+            *c |= *o;
+        }
+    }
+
+    /// Total number of edges between a "value" cell and a cell
+    /// with the opposite value or the outside of the grid,
+    /// summed over all the regions of "value".
+    pub fn perimeter(&self, value: bool) -> usize {
+        let mut perimeter = 0;
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get(x, y) != value {
+                    continue;
+                }
+                perimeter += self
+                    .neighbors4_signed(x as isize, y as isize)
+                    .into_iter()
+                    .filter(|(nx, ny)| self.checked_get(*nx, *ny) != Some(value))
+                    .count();
+            }
+        }
+        perimeter
+    }
+}
+
+/// Record the directions used when visiting each cell of a grid,
+/// as a bitmap of Direction::bit() per cell.
+/// Typically used to detect a loop when a path passes again on a
+/// cell in the same direction (only crossing it is not a loop).
+#[derive(Clone)]
+pub struct DirectionVisited {
+    visited: Grid<u8>,
+}
+
+impl DirectionVisited {
+    pub fn new(width: usize, height: usize) -> Self {
+        DirectionVisited {
+            visited: Grid::<u8>::new(width, height, 0),
+        }
+    }
+
+    /// Mark the cell as visited in this direction.
+    /// Returns true if it was already visited in the same direction.
+    pub fn mark(&mut self, x: usize, y: usize, dir: Direction) -> bool {
+        let old = self.visited.get(x, y);
+        self.visited.set(x, y, old | dir.bit());
+        old & dir.bit() != 0
+    }
+
+    /// Check if the cell was visited in this direction, without marking it.
+    pub fn is_visited(&self, x: usize, y: usize, dir: Direction) -> bool {
+        self.visited.get(x, y) & dir.bit() != 0
+    }
+
+    /// Forget all the visits.
+    pub fn clear(&mut self) {
+        self.visited.fill(0);
+    }
+}
+
+/// A dense boolean map packing 64 cells per u64, for large
+/// visited-sets where a Grid<bool> would use a byte per cell.
+#[derive(Clone)]
+pub struct BitGrid {
+    pub width: usize,
+    pub height: usize,
+    bits: Box<[u64]>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        BitGrid {
+            width,
+            height,
+            bits: vec![0; (width * height).div_ceil(64)].into_boxed_slice(),
+        }
+    }
+
+    // Word index and bit mask of a cell
+    fn locate(&self, x: usize, y: usize) -> (usize, u64) {
+        if x >= self.width || y >= self.height {
+            panic!("array access {},{} out of bounds", x, y);
+        }
+        let i = x + y * self.width;
+        (i / 64, 1 << (i % 64))
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        let (word, mask) = self.locate(x, y);
+        self.bits[word] & mask != 0
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, value: bool) {
+        let (word, mask) = self.locate(x, y);
+        if value {
+            self.bits[word] |= mask;
+        } else {
+            self.bits[word] &= !mask;
+        }
+    }
+
+    /// Number of cells set to true.
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().map(|w| w.count_ones() as usize).sum()
+    }
+}
+
+/// A builder to construct a Grid by parsing lines
+/// one by one (without knowing the final size)
+/// (Note: this is not strictly the Builder Pattern, needs a better name ?)
+#[derive(Clone)]
+pub struct GridBuilder<T> {
+    width: usize,
+    height: usize,
+    s: Vec<T>,
+}
+
+impl<T: std::clone::Clone> Default for GridBuilder<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: std::clone::Clone> GridBuilder<T> {
+    /// Create an initially empty builder.
+    pub fn new() -> Self {
+        GridBuilder {
+            width: 0,
+            height: 0,
+            s: Vec::<T>::new(),
+        }
+    }
+
+    /// Add a new row at the end of the builder.
+    /// When it's the first time, this row defines the width
+    /// of the grid. All other rows must have the same width
+    /// else a panic is emitted.
+    pub fn append_line(&mut self, line: &[T]) {
+        if self.height == 0 {
+            self.width = line.len();
+        } else if self.width != line.len() {
+            panic!(
+                "Row of len {} appended to GridBuilder of width {}",
+                line.len(),
+                self.width
+            );
+        }
+
+        self.height += 1;
+        self.s.extend_from_slice(line);
+    }
+
+    /// Same as append_line(), but rows shorter than the width are
+    /// padded at the end with "pad" (for example input lines with
+    /// trailing spaces trimmed). Longer rows still emit a panic.
+    pub fn append_line_padded(&mut self, line: &[T], pad: T) {
+        if self.height == 0 || line.len() >= self.width {
+            self.append_line(line);
+        } else {
+            self.height += 1;
+            self.s.extend_from_slice(line);
+            self.s.resize(self.height * self.width, pad);
+        }
+    }
+
+    /// Convert into the final Grid when nothing else needs appending.
+    pub fn to_grid(self) -> Grid<T> {
+        if self.s.is_empty() {
             panic!("GridBuilder is still empty");
         }
         Grid::<T> {
@@ -490,6 +1503,53 @@ mod test {
         assert_eq!(grid.get(3, 2), 888);
     }
 
+    #[test]
+    fn grid_builder_padded() {
+        let mut gb = GridBuilder::<char>::new();
+        gb.append_line_padded(&['#', '.', '#'], ' ');
+        gb.append_line_padded(&['#'], ' ');
+        gb.append_line_padded(&[], ' ');
+        gb.append_line_padded(&['.', '.', '.'], ' ');
+        let grid = gb.to_grid();
+
+        crate::testutil::assert_grid_eq(&grid, "#.#\n#  \n   \n...");
+    }
+
+    #[test]
+    #[should_panic(expected = "Row of len 4 appended to GridBuilder of width 3")]
+    fn grid_builder_padded_too_long() {
+        let mut gb = GridBuilder::<char>::new();
+        gb.append_line_padded(&['#', '.', '#'], ' ');
+        gb.append_line_padded(&['#', '.', '#', '.'], ' ');
+    }
+
+    #[test]
+    fn grid_index() {
+        let mut grid = crate::testutil::grid_from_str("abc\ndef");
+        assert_eq!(grid[(0, 0)], 'a');
+        assert_eq!(grid[(2, 1)], 'f');
+        grid[(1, 1)] = 'x';
+        grid[(2, 0)].make_ascii_uppercase();
+        assert_eq!(grid[(1, 1)], 'x');
+        assert_eq!(grid.get(1, 1), 'x');
+        assert_eq!(grid.checked_get(2, 0), Some('C'));
+        crate::testutil::assert_grid_eq(&grid, "abC\ndxf");
+    }
+
+    #[test]
+    #[should_panic(expected = "array access 3,0 out of bounds")]
+    fn grid_index_out_of_bounds() {
+        let grid = Grid::<u8>::new(3, 2, 0);
+        let _ = grid[(3, 0)];
+    }
+
+    #[test]
+    #[should_panic(expected = "array access 0,2 out of bounds")]
+    fn grid_index_mut_out_of_bounds() {
+        let mut grid = Grid::<u8>::new(3, 2, 0);
+        grid[(0, 2)] = 1;
+    }
+
     #[derive(Clone, PartialEq, Eq, Debug)]
     struct Elmt {
         v: usize,
@@ -536,6 +1596,905 @@ mod test {
         grid.pretty_print_lambda(&|e: Elmt| format!("{:02}_{}|", e.v, e.dir));
     }
 
+    #[test]
+    fn grid_flood_fill_while() {
+        // day 10 sample: trails climb by exactly 1 from each 0 to 9
+        let trails = crate::testutil::grid_from_str(
+            "89010123\n78121874\n87430965\n96549874\n45678903\n32019012\n01329801\n10456732",
+        );
+        let mut total_score = 0;
+        for y in 0..trails.height {
+            for x in 0..trails.width {
+                if trails.get(x, y) == '0' {
+                    total_score += trails
+                        .flood_fill_while((x, y), |c, n| *n as u32 == *c as u32 + 1)
+                        .iter()
+                        .filter(|(x, y)| trails.get(*x, *y) == '9')
+                        .count();
+                }
+            }
+        }
+        assert_eq!(total_score, 36);
+
+        // day 12 first sample: region growth by equal plant
+        let garden = crate::testutil::grid_from_str("AAAA\nBBCD\nBBCC\nEEEC");
+        let mut region = garden.flood_fill_while((2, 1), |c, n| c == n);
+        assert_eq!(region.len(), 4);
+        region.sort();
+        assert_eq!(region, vec![(2, 1), (2, 2), (3, 2), (3, 3)]);
+        assert_eq!(garden.flood_fill_while((0, 0), |c, n| c == n).len(), 4);
+        assert_eq!(garden.flood_fill_while((3, 1), |c, n| c == n), vec![(3, 1)]);
+    }
+
+    #[test]
+    fn grid_flood_fill() {
+        // two separate regions of 'A'
+        let garden = crate::testutil::grid_from_str("AAB\nBBB\nBAA");
+        let mut region = flood_fill(&garden, (1, 0));
+        region.sort();
+        assert_eq!(region, vec![(0, 0), (1, 0)]);
+
+        let mut region = flood_fill(&garden, (2, 2));
+        region.sort();
+        assert_eq!(region, vec![(1, 2), (2, 2)]);
+
+        assert_eq!(flood_fill(&garden, (0, 1)).len(), 5);
+
+        // large region, would overflow a recursive fill
+        let big = Grid::<u8>::new(1000, 1000, 0);
+        assert_eq!(flood_fill(&big, (500, 500)).len(), 1_000_000);
+    }
+
+    #[test]
+    fn grid_to_writer() {
+        let mut gb = GridBuilder::<usize>::new();
+        gb.append_line(&[1, 2, 3]);
+        gb.append_line(&[4, 5, 6]);
+        let grid = gb.to_grid();
+
+        let mut buf = Vec::<u8>::new();
+        grid.to_writer(&mut buf).unwrap();
+        assert_eq!(buf, b"[3,2] = \n[ 1 2 3 ]\n[ 4 5 6 ]\n");
+
+        let mut buf = Vec::<u8>::new();
+        let parity = |v: usize| if v.is_multiple_of(2) { "#" } else { "." }.to_string();
+        grid.to_writer_lambda(&mut buf, &parity).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "[3,2] = \n[.#.] \n[#.#] \n"
+        );
+    }
+
+    #[test]
+    fn grid_enclosed_cells() {
+        let mut gb = GridBuilder::<bool>::new();
+        for row in ["......", ".###..", ".#.#..", ".####.", "....#.", "....#."] {
+            gb.append_char_map(row, '#');
+        }
+        let grid = gb.to_grid();
+
+        // The pocket at (2,2) is closed; the cell at (5,5)
+        // is open by touching the border.
+        assert_eq!(grid.enclosed_cells(), vec![(2, 2)]);
+    }
+
+    #[test]
+    fn grid_rotate_values() {
+        let mut gb = GridBuilder::<usize>::new();
+        gb.append_line(&[1, 2, 3, 4]);
+        gb.append_line(&[5, 6, 7, 8]);
+        gb.append_line(&[9, 10, 11, 12]);
+        let mut grid = gb.to_grid();
+
+        grid.rotate_row(1, 1);
+        assert_eq!(grid.get(0, 1), 8);
+        assert_eq!(grid.get(1, 1), 5);
+        assert_eq!(grid.get(3, 1), 7);
+        grid.rotate_row(1, -1);
+        assert_eq!(grid.get(0, 1), 5);
+        assert_eq!(grid.get(3, 1), 8);
+        // a full cycle is a no-op
+        grid.rotate_row(0, 4);
+        assert_eq!(grid.get(0, 0), 1);
+
+        grid.rotate_column(2, 1);
+        assert_eq!(grid.get(2, 0), 11);
+        assert_eq!(grid.get(2, 1), 3);
+        assert_eq!(grid.get(2, 2), 7);
+        grid.rotate_column(2, -2);
+        assert_eq!(grid.get(2, 0), 7);
+        assert_eq!(grid.get(2, 1), 11);
+        assert_eq!(grid.get(2, 2), 3);
+        // other columns untouched
+        assert_eq!(grid.get(1, 0), 2);
+    }
+
+    #[test]
+    fn grid_neighbors_custom() {
+        let grid = Grid::<u8>::new(4, 3, 0);
+        let diagonals = [(-1, -1), (1, -1), (-1, 1), (1, 1)];
+        assert_eq!(grid.neighbors_custom(0, 0, &diagonals), vec![(1, 1)]);
+        assert_eq!(grid.neighbors_custom(3, 2, &diagonals), vec![(2, 1)]);
+        assert_eq!(
+            grid.neighbors_custom(1, 1, &diagonals),
+            vec![(0, 0), (2, 0), (0, 2), (2, 2)]
+        );
+
+        let knight = [(1, 2), (2, 1), (-1, 2), (-2, 1)];
+        assert_eq!(grid.neighbors_custom(0, 0, &knight), vec![(1, 2), (2, 1)]);
+    }
+
+    #[test]
+    fn grid_neighbors4_neighbors8() {
+        let grid = Grid::<u8>::new(4, 3, 0);
+
+        // corner
+        assert_eq!(grid.neighbors4(0, 0), vec![(1, 0), (0, 1)]);
+        assert_eq!(grid.neighbors8(0, 0), vec![(1, 0), (1, 1), (0, 1)]);
+        // edge
+        assert_eq!(grid.neighbors4(3, 1), vec![(2, 1), (3, 2), (3, 0)]);
+        assert_eq!(grid.neighbors8(3, 1).len(), 5);
+        // center
+        assert_eq!(grid.neighbors4(1, 1), vec![(2, 1), (0, 1), (1, 2), (1, 0)]);
+        let n8 = grid.neighbors8(1, 1);
+        assert_eq!(n8.len(), 8);
+        assert!(!n8.contains(&(1, 1)));
+    }
+
+    #[test]
+    fn grid_matching_neighbors() {
+        let grid = crate::testutil::grid_from_str("#.#\n##.\n..#").to_bool('#');
+        // center
+        assert_eq!(grid.matching_neighbors4(1, 1, |v| *v), 1);
+        assert_eq!(grid.matching_neighbors8(1, 1, |v| *v), 4);
+        // corner
+        assert_eq!(grid.matching_neighbors4(0, 0, |v| *v), 1);
+        assert_eq!(grid.matching_neighbors8(0, 0, |v| *v), 2);
+        assert_eq!(grid.matching_neighbors8(2, 2, |v| !*v), 2);
+    }
+
+    #[test]
+    fn grid_diagonal_neighbors() {
+        let grid = crate::testutil::grid_from_str("M.S\n.A.\nM.S");
+        assert_eq!(
+            grid.diagonal_neighbors(1, 1),
+            [Some('M'), Some('S'), Some('M'), Some('S')]
+        );
+        assert_eq!(grid.diagonal_neighbors(0, 0), [None, None, None, Some('A')]);
+    }
+
+    #[test]
+    fn grid_step_blinker() {
+        let life = |alive: &bool, neighbors: &[Option<bool>; 8]| {
+            let n = neighbors.iter().filter(|v| **v == Some(true)).count();
+            n == 3 || (*alive && n == 2)
+        };
+        let start =
+            crate::testutil::grid_from_str(".....\n..#..\n..#..\n..#..\n.....").to_bool('#');
+
+        let once = start.step(life);
+        assert_eq!(once.true_coords(), vec![(1, 2), (2, 2), (3, 2)]);
+        let twice = once.step(life);
+        assert_eq!(twice.true_coords(), start.true_coords());
+    }
+
+    #[test]
+    fn grid_char_to_bool() {
+        let mut gb = GridBuilder::<char>::new();
+        for row in ["#####", "#S.E#", "#.#.#", "#####"] {
+            gb.append_line(&row.chars().collect::<Vec<char>>());
+        }
+        let walls = gb.to_grid().to_bool('#');
+
+        assert_eq!((walls.width, walls.height), (5, 4));
+        assert!(walls.get(0, 0));
+        assert!(walls.get(2, 2));
+        assert!(!walls.get(1, 1));
+        assert!(!walls.get(3, 2));
+    }
+
+    #[test]
+    fn grid_try_fill_with() {
+        let rows = ["123", "45x"];
+        let parse = |x: usize, y: usize| {
+            let c = rows[y].as_bytes()[x] as char;
+            c.to_digit(10).ok_or(format!("bad digit {c} at {x},{y}"))
+        };
+
+        let mut grid = Grid::<u32>::new(3, 2, 0);
+        assert_eq!(
+            grid.try_fill_with(parse),
+            Err("bad digit x at 2,1".to_string())
+        );
+        assert_eq!(grid.get(0, 0), 0);
+
+        let rows = ["123", "456"];
+        let parse = |x: usize, y: usize| {
+            let c = rows[y].as_bytes()[x] as char;
+            c.to_digit(10).ok_or(format!("bad digit {c} at {x},{y}"))
+        };
+        assert_eq!(grid.try_fill_with(parse), Ok(()));
+        assert_eq!(grid.get(0, 0), 1);
+        assert_eq!(grid.get(2, 0), 3);
+        assert_eq!(grid.get(0, 1), 4);
+        assert_eq!(grid.get(2, 1), 6);
+    }
+
+    #[test]
+    fn grid_count_paths_monotone() {
+        // day 10 sample, total rating of all the trailheads
+        let chars = crate::testutil::grid_from_str(
+            "89010123\n78121874\n87430965\n96549874\n45678903\n32019012\n01329801\n10456732",
+        );
+        let mut elevations = Grid::<usize>::new(chars.width, chars.height, 0);
+        elevations
+            .try_fill_with(|x, y| chars.get(x, y).to_digit(10).map(|d| d as usize).ok_or(()))
+            .unwrap();
+
+        let mut ratings = Vec::new();
+        for y in 0..elevations.height {
+            for x in 0..elevations.width {
+                if elevations.get(x, y) == 0 {
+                    ratings.push(elevations.count_paths_monotone(
+                        (x, y),
+                        |c, n| *n == c + 1,
+                        |v| *v == 9,
+                    ));
+                }
+            }
+        }
+        assert_eq!(ratings, vec![20, 24, 10, 4, 1, 4, 5, 8, 5]);
+        assert_eq!(ratings.iter().sum::<usize>(), 81);
+    }
+
+    #[test]
+    fn grid_true_coords_and_set() {
+        let mask = crate::testutil::grid_from_str("#..\n.##").to_bool('#');
+        assert_eq!(mask.true_coords(), vec![(0, 0), (1, 1), (2, 1)]);
+
+        let set = mask.true_set();
+        assert_eq!(set.len(), 3);
+        assert!(set.contains(&(2, 1)));
+        assert!(!set.contains(&(1, 0)));
+
+        let other = crate::testutil::grid_from_str("##.\n..#").to_bool('#');
+        let mut common: Vec<_> = set.intersection(&other.true_set()).cloned().collect();
+        common.sort();
+        assert_eq!(common, vec![(0, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn grid_overlay_in_place() {
+        let mut mask = crate::testutil::grid_from_str("#..\n.#.").to_bool('#');
+        let other = crate::testutil::grid_from_str("#.#\n...").to_bool('#');
+        mask.overlay_in_place(&other);
+        assert!(mask.get(0, 0));
+        assert!(!mask.get(1, 0));
+        assert!(mask.get(2, 0));
+        assert!(!mask.get(0, 1));
+        assert!(mask.get(1, 1));
+        assert!(!mask.get(2, 1));
+    }
+
+    #[test]
+    #[should_panic]
+    fn grid_overlay_in_place_mismatch() {
+        let mut mask = Grid::<bool>::new(3, 2, false);
+        mask.overlay_in_place(&Grid::<bool>::new(2, 3, true));
+    }
+
+    #[test]
+    fn grid_follow_single_path() {
+        let track = crate::testutil::grid_from_str(
+            "#####\n\
+             #S..#\n\
+             ###.#\n\
+             #...#\n\
+             #.###\n\
+             #..E#\n\
+             #####",
+        )
+        .to_bool('#');
+        let path = track.follow_single_path((1, 1), (3, 5));
+        assert_eq!(
+            path,
+            vec![
+                (1, 1),
+                (2, 1),
+                (3, 1),
+                (3, 2),
+                (3, 3),
+                (2, 3),
+                (1, 3),
+                (1, 4),
+                (1, 5),
+                (2, 5),
+                (3, 5)
+            ]
+        );
+        assert_eq!(track.follow_single_path((3, 3), (3, 3)), vec![(3, 3)]);
+    }
+
+    #[test]
+    fn grid_farthest_reachable() {
+        let corridor = crate::testutil::grid_from_str(
+            "#######\n\
+             #.....#\n\
+             #####.#\n\
+             #.....#\n\
+             #.#####",
+        )
+        .to_bool('#');
+        assert_eq!(corridor.farthest_reachable((1, 1)), Some(((1, 4), 11)));
+        assert_eq!(corridor.farthest_reachable((5, 2)), Some(((1, 4), 6)));
+        assert_eq!(corridor.farthest_reachable((0, 0)), None);
+
+        let single = Grid::<bool>::new(1, 1, false);
+        assert_eq!(single.farthest_reachable((0, 0)), Some(((0, 0), 0)));
+    }
+
+    #[test]
+    fn grid_direction_visited() {
+        let mut visited = DirectionVisited::new(3, 2);
+        assert!(!visited.mark(1, 1, Direction::Up));
+        assert!(visited.is_visited(1, 1, Direction::Up));
+        // crossing in another direction is not a revisit
+        assert!(!visited.mark(1, 1, Direction::Left));
+        assert!(!visited.is_visited(1, 1, Direction::Down));
+        assert!(!visited.is_visited(1, 0, Direction::Up));
+        assert!(visited.mark(1, 1, Direction::Up));
+        assert!(visited.mark(1, 1, Direction::Left));
+
+        visited.clear();
+        assert!(!visited.mark(1, 1, Direction::Up));
+    }
+
+    #[test]
+    fn grid_bit_grid() {
+        let mut bits = BitGrid::new(100, 7);
+        let scattered = [(0, 0), (63, 0), (64, 0), (99, 3), (5, 6), (99, 6)];
+        for (x, y) in scattered {
+            bits.set(x, y, true);
+        }
+        assert_eq!(bits.count_ones(), 6);
+        for (x, y) in scattered {
+            assert!(bits.get(x, y));
+        }
+        assert!(!bits.get(1, 0));
+        assert!(!bits.get(62, 0));
+        assert!(!bits.get(0, 1));
+        assert!(!bits.get(98, 6));
+
+        bits.set(64, 0, false);
+        bits.set(63, 0, true);
+        assert!(!bits.get(64, 0));
+        assert_eq!(bits.count_ones(), 5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn grid_bit_grid_out_of_bounds() {
+        let bits = BitGrid::new(10, 10);
+        bits.get(10, 0);
+    }
+
+    #[test]
+    fn grid_bool_perimeter() {
+        let single = Grid::<bool>::new(1, 1, true);
+        assert_eq!(single.perimeter(true), 4);
+        assert_eq!(single.perimeter(false), 0);
+
+        let mut block = Grid::<bool>::new(4, 4, false);
+        block.blit(&Grid::<bool>::new(2, 2, true), 1, 1);
+        assert_eq!(block.perimeter(true), 8);
+        // 16 outer border edges + 8 around the block
+        assert_eq!(block.perimeter(false), 24);
+
+        // two regions: 2x2 in a corner and a lone cell
+        let mut regions = Grid::<bool>::new(4, 3, false);
+        regions.blit(&Grid::<bool>::new(2, 2, true), 0, 0);
+        regions.set(3, 2, true);
+        assert_eq!(regions.perimeter(true), 12);
+    }
+
+    #[test]
+    fn grid_get_row_slice_not_square() {
+        let mut gb = GridBuilder::<usize>::new();
+        gb.append_line(&[1, 2, 3, 4, 5]);
+        gb.append_line(&[6, 7, 8, 9, 10]);
+        let grid = gb.to_grid();
+
+        assert_eq!(grid.get_row_slice(0), &[1, 2, 3, 4, 5]);
+        assert_eq!(grid.get_row_slice(1), &[6, 7, 8, 9, 10]);
+    }
+
+    #[test]
+    fn grid_new_with_border() {
+        let grid = Grid::new_with_border(3, 2, '.', '#');
+        assert_eq!(grid.width, 5);
+        assert_eq!(grid.height, 4);
+        crate::testutil::assert_grid_eq(&grid, "#####\n#...#\n#...#\n#####");
+    }
+
+    #[test]
+    fn grid_is_border() {
+        let grid = Grid::<u8>::new(4, 3, 0);
+        for (x, y) in [(0, 0), (3, 0), (0, 2), (3, 2)] {
+            assert!(grid.is_border(x, y), "corner {x},{y}");
+        }
+        for (x, y) in [(1, 0), (2, 2), (0, 1), (3, 1)] {
+            assert!(grid.is_border(x, y), "edge {x},{y}");
+        }
+        assert!(!grid.is_border(1, 1));
+        assert!(!grid.is_border(2, 1));
+    }
+
+    #[test]
+    fn grid_iter_coords_rev() {
+        let grid = crate::testutil::grid_from_str("abc\ndef");
+        let cells: Vec<(usize, usize, char)> =
+            grid.iter_coords_rev().map(|(x, y, v)| (x, y, *v)).collect();
+        assert_eq!(cells.len(), 6);
+        assert_eq!(cells[0], (2, 1, 'f'));
+        assert_eq!(cells[3], (2, 0, 'c'));
+        assert_eq!(cells[5], (0, 0, 'a'));
+    }
+
+    #[test]
+    fn grid_border_coords() {
+        let grid = Grid::<u8>::new(3, 3, 0);
+        let border: Vec<(usize, usize)> = grid.border_coords().collect();
+        assert_eq!(
+            border,
+            vec![
+                (0, 0),
+                (1, 0),
+                (2, 0),
+                (2, 1),
+                (2, 2),
+                (1, 2),
+                (0, 2),
+                (0, 1)
+            ]
+        );
+        assert!(border.iter().all(|(x, y)| grid.is_border(*x, *y)));
+
+        let column = Grid::<u8>::new(1, 4, 0);
+        let border: Vec<(usize, usize)> = column.border_coords().collect();
+        assert_eq!(border, vec![(0, 0), (0, 1), (0, 2), (0, 3)]);
+
+        let row = Grid::<u8>::new(4, 1, 0);
+        assert_eq!(row.border_coords().count(), 4);
+        assert_eq!(Grid::<u8>::new(5, 4, 0).border_coords().count(), 14);
+        assert_eq!(Grid::<u8>::new(0, 0, 0).border_coords().count(), 0);
+    }
+
+    #[test]
+    fn grid_iter_column() {
+        let mut gb = GridBuilder::<usize>::new();
+        gb.append_line(&[1, 2, 3, 4]);
+        gb.append_line(&[10, 20, 30, 40]);
+        gb.append_line(&[100, 200, 300, 400]);
+        let grid = gb.to_grid();
+
+        let col: Vec<usize> = grid.iter_column(0).copied().collect();
+        assert_eq!(col, vec![1, 10, 100]);
+        assert_eq!(grid.column_to_vec(3), vec![4, 40, 400]);
+        assert_eq!(grid.iter_column(2).count(), 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "array column 4 out of bounds")]
+    fn grid_iter_column_out_of_bounds() {
+        let grid = Grid::<u8>::new(4, 3, 0);
+        let _ = grid.iter_column(4);
+    }
+
+    #[test]
+    fn grid_row_mut() {
+        let mut grid = crate::testutil::grid_from_str("abcd\nefgh\nijkl");
+        grid.row_mut(1).copy_from_slice(&['w', 'x', 'y', 'z']);
+        grid.row_mut(2).reverse();
+        grid.row_mut(0)[3] = '!';
+        crate::testutil::assert_grid_eq(&grid, "abc!\nwxyz\nlkji");
+    }
+
+    #[test]
+    #[should_panic(expected = "array row 3 out of bounds")]
+    fn grid_row_mut_out_of_bounds() {
+        let mut grid = Grid::<u8>::new(4, 3, 0);
+        grid.row_mut(3);
+    }
+
+    #[test]
+    fn grid_row_windows() {
+        let mut gb = GridBuilder::<usize>::new();
+        gb.append_line(&[1, 2, 3, 4, 5]);
+        gb.append_line(&[10, 20, 30, 40, 50]);
+        let grid = gb.to_grid();
+
+        let windows: Vec<&[usize]> = grid.row_windows(1, 3).collect();
+        assert_eq!(windows, vec![&[10, 20, 30], &[20, 30, 40], &[30, 40, 50]]);
+        assert_eq!(grid.row_windows(0, 5).count(), 1);
+        assert_eq!(grid.row_windows(0, 6).count(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "row window size must be at least 1")]
+    fn grid_row_windows_empty() {
+        let grid = Grid::<usize>::new(3, 2, 0);
+        grid.row_windows(0, 0).count();
+    }
+
+    #[test]
+    fn grid_diff() {
+        use crate::testutil::grid_from_str;
+
+        let a = grid_from_str("abc\ndef\nghi");
+        let b = grid_from_str("abc\nxef\nghy");
+        assert_eq!(a.diff(&b), vec![(0, 1), (2, 2)]);
+        assert_eq!(a.diff(&a), vec![]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn grid_diff_dimensions() {
+        use crate::testutil::grid_from_str;
+
+        grid_from_str("abc\ndef").diff(&grid_from_str("ab\nde"));
+    }
+
+    #[test]
+    fn grid_rows_within_hamming() {
+        let mut gb = GridBuilder::<bool>::new();
+        gb.append_line(&[true, true, false, false, true]);
+        // same
+        gb.append_line(&[true, true, false, false, true]);
+        // 1 difference
+        gb.append_line(&[true, false, false, false, true]);
+        // 3 differences
+        gb.append_line(&[false, true, true, true, true]);
+        let grid = gb.to_grid();
+
+        assert_eq!(grid.rows_within_hamming(0, 1), vec![0, 1, 2]);
+        assert_eq!(grid.rows_within_hamming(0, 0), vec![0, 1]);
+        assert_eq!(grid.rows_within_hamming(3, 1), vec![3]);
+        assert_eq!(grid.rows_within_hamming(0, 3).len(), 4);
+    }
+
+    #[test]
+    fn grid_region_count() {
+        use crate::testutil::grid_from_str;
+
+        // each cell of a checkerboard is its own region
+        let checkerboard = grid_from_str("abab\nbaba\nabab");
+        assert_eq!(region_count(&checkerboard), 12);
+        assert_eq!(region_count(&grid_from_str("aab\nabb")), 2);
+    }
+
+    #[test]
+    fn grid_label_regions() {
+        let garden = crate::testutil::grid_from_str("AAB\nAAC\nDDC");
+        let (labels, count) = garden.label_regions();
+        assert_eq!(count, 4);
+        assert_eq!(labels.get(0, 0), 0);
+        assert_eq!(labels.get(1, 1), 0);
+        assert_eq!(labels.get(2, 0), 1);
+        assert_eq!(labels.get(2, 1), 2);
+        assert_eq!(labels.get(2, 2), 2);
+        assert_eq!(labels.get(0, 2), 3);
+    }
+
+    #[test]
+    fn grid_flood_region_detailed() {
+        let garden = crate::testutil::grid_from_str("AABB\nAABB\nAAAA\nAAAA");
+        let mut region = garden.flood_region_detailed((0, 3));
+        assert_eq!(region.area, 12);
+        assert_eq!(region.cells.len(), 12);
+        assert_eq!(region.perimeter, 16);
+        assert_eq!(region.border_cells.len(), 11);
+        assert!(!region.border_cells.contains(&(1, 2)));
+        region.cells.sort();
+        assert_eq!(region.cells[0], (0, 0));
+        assert_eq!(region.cells[11], (3, 3));
+        assert!(!region.cells.contains(&(2, 1)));
+
+        let region = garden.flood_region_detailed((3, 1));
+        assert_eq!(region.area, 4);
+        assert_eq!(region.perimeter, 8);
+        assert_eq!(region.border_cells.len(), 4);
+    }
+
+    #[test]
+    fn grid_map_region() {
+        let mut garden = crate::testutil::grid_from_str("AAB\nBAB\nBBA");
+        garden.map_region((1, 1), |c| c.to_ascii_lowercase());
+        crate::testutil::assert_grid_eq(&garden, "aaB\nBaB\nBBA");
+
+        // regions of the same value but disconnected are untouched
+        garden.map_region((2, 0), |_| '*');
+        crate::testutil::assert_grid_eq(&garden, "aa*\nBa*\nBBA");
+    }
+
+    #[test]
+    fn grid_map() {
+        let mut gb = GridBuilder::<u8>::new();
+        gb.append_line(&[1, 2, 3]);
+        gb.append_line(&[4, 5, 6]);
+        gb.append_line(&[7, 8, 9]);
+        let digits = gb.to_grid();
+
+        let even = digits.map(|d| d.is_multiple_of(2));
+        assert_eq!((even.width, even.height), (3, 3));
+        assert!(!even.get(0, 0));
+        assert!(even.get(1, 0));
+        assert!(even.get(0, 1));
+        assert!(!even.get(1, 1));
+        assert!(even.get(1, 2));
+        assert!(!even.get(2, 2));
+
+        let unit = digits.map(|_| ());
+        assert_eq!((unit.width, unit.height), (3, 3));
+        assert_eq!(unit.checked_get(2, 2), Some(()));
+    }
+
+    #[test]
+    fn grid_count_by() {
+        let mut gb = GridBuilder::<usize>::new();
+        gb.append_line(&[1, 2, 3]);
+        gb.append_line(&[4, 6, 8]);
+        let grid = gb.to_grid();
+
+        let parity = grid.count_by(|v| v.is_multiple_of(2));
+        assert_eq!(parity.len(), 2);
+        assert_eq!(parity[&true], 4);
+        assert_eq!(parity[&false], 2);
+
+        let all = grid.count_by(|_| ());
+        assert_eq!(all[&()], 6);
+    }
+
+    #[test]
+    fn grid_min_max_by_key() {
+        let mut gb = GridBuilder::<u8>::new();
+        gb.append_line(&[1, 8, 7]);
+        gb.append_line(&[3, 4, 0]);
+        gb.append_line(&[5, 2, 6]);
+        let grid = gb.to_grid();
+
+        // most set bits: 7 (0b111) wins over 3, 5 and 6
+        assert_eq!(grid.max_by_key(|v| v.count_ones()), Some(((2, 0), 7)));
+        assert_eq!(grid.max_by_key(|v| *v), Some(((1, 0), 8)));
+        assert_eq!(grid.min_by_key(|v| *v), Some(((2, 1), 0)));
+        // tie between 7 and 3: first in reading order
+        assert_eq!(grid.max_by_key(|v| v % 4), Some(((2, 0), 7)));
+
+        let empty = Grid::<u8>::new(0, 0, 0);
+        assert_eq!(empty.max_by_key(|v| *v), None);
+    }
+
+    #[test]
+    fn grid_transitions() {
+        let grid = crate::testutil::grid_from_str("AAB\nABB\nCCC");
+        assert_eq!(grid.horizontal_transitions(|a, b| a != b), 2);
+        assert_eq!(grid.vertical_transitions(|a, b| a != b), 4);
+        assert_eq!(grid.horizontal_transitions(|a, b| a == b), 4);
+        assert_eq!(grid.vertical_transitions(|a, b| a == b), 2);
+        // only the changes from A to B, left to right
+        assert_eq!(
+            grid.horizontal_transitions(|a, b| *a == 'A' && *b == 'B'),
+            2
+        );
+    }
+
+    #[test]
+    fn grid_bounding_box() {
+        let grid = crate::testutil::grid_from_str("......\n...#..\n.#....\n....#.\n......");
+        assert_eq!(grid.bounding_box(|c| *c == '#'), Some((1, 1, 4, 3)));
+        assert_eq!(grid.bounding_box(|c| *c == 'x'), None);
+        assert_eq!(grid.bounding_box(|c| *c == '.'), Some((0, 0, 5, 4)));
+    }
+
+    #[test]
+    fn grid_region_size_histogram() {
+        let garden = crate::testutil::grid_from_str("AAB\nAAC");
+        let histogram = garden.region_size_histogram();
+        assert_eq!(histogram.len(), 2);
+        assert_eq!(histogram[&4], 1);
+        assert_eq!(histogram[&1], 2);
+    }
+
+    #[test]
+    fn grid_write_ppm() {
+        let grid = crate::testutil::grid_from_str("#.\n.#");
+        let mut out = Vec::<u8>::new();
+        grid.write_ppm(
+            &mut out,
+            |c| if *c == '#' { (255, 0, 0) } else { (0, 0, 16) },
+        )
+        .unwrap();
+
+        let header = b"P6\n2 2\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(
+            &out[header.len()..],
+            &[255, 0, 0, 0, 0, 16, 0, 0, 16, 255, 0, 0]
+        );
+    }
+
+    #[test]
+    fn grid_sum_product() {
+        let mut gb = GridBuilder::<usize>::new();
+        gb.append_line(&[1, 2, 3]);
+        gb.append_line(&[4, 5, 6]);
+        let grid = gb.to_grid();
+        assert_eq!(grid.sum(), 21);
+        assert_eq!(grid.product(), 720);
+
+        let signed = Grid::<i64>::new(2, 2, -3);
+        assert_eq!(signed.sum(), -12);
+        assert_eq!(signed.product(), 81);
+
+        // identities on an empty grid
+        let empty = Grid::<usize>::new(0, 3, 7);
+        assert_eq!(empty.sum(), 0);
+        assert_eq!(empty.product(), 1);
+    }
+
+    #[test]
+    fn grid_heat_writer() {
+        let mut distances = Grid::<usize>::new(3, 2, usize::MAX);
+        distances.set(0, 0, 0);
+        distances.set(1, 0, 1);
+        distances.set(2, 0, 2);
+        distances.set(2, 1, 13);
+
+        let mut out = Vec::<u8>::new();
+        distances.to_writer_heat(&mut out, 0, 13, true).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let blue = format!("{}0{}", colors::BG_COLORS[colors::BLUE], colors::ANSI_RESET);
+        let magenta = format!(
+            "{}3{}",
+            colors::BG_COLORS[colors::MAGENTA],
+            colors::ANSI_RESET
+        );
+        assert!(out.contains(&blue));
+        assert!(out.contains(&magenta));
+        assert!(out.contains("[..\x1B"));
+
+        let mut out = Vec::<u8>::new();
+        distances.to_writer_heat(&mut out, 0, 13, false).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "[3,2] = \n[012] \n[..3] \n"
+        );
+    }
+
+    #[test]
+    fn grid_u16_threshold() {
+        // non-square to catch row/column mixups
+        let mut generations = Grid::<u16>::new(3, 2, u16::MAX);
+        generations.set(0, 0, 3);
+        generations.set(2, 0, 1);
+        generations.set(1, 1, 2);
+        generations.set(2, 1, 5);
+
+        let walls = generations.threshold(2);
+        assert_eq!((walls.width, walls.height), (3, 2));
+        assert!(!walls.get(0, 0));
+        assert!(!walls.get(1, 0));
+        assert!(walls.get(2, 0));
+        assert!(!walls.get(0, 1));
+        assert!(walls.get(1, 1));
+        assert!(!walls.get(2, 1));
+
+        assert!(generations.threshold(0).s.iter().all(|w| !w));
+        assert!(generations.threshold(u16::MAX).s.iter().all(|w| *w));
+    }
+
+    #[test]
+    fn grid_resize() {
+        let grid = crate::testutil::grid_from_str("abc\ndef\nghi");
+        crate::testutil::assert_grid_eq(&grid.resize(2, 2, '.'), "ab\nde");
+        crate::testutil::assert_grid_eq(&grid.resize(4, 4, '.'), "abc.\ndef.\nghi.\n....");
+        crate::testutil::assert_grid_eq(&grid.resize(5, 1, '.'), "abc..");
+    }
+
+    #[test]
+    fn grid_shift() {
+        let grid = crate::testutil::grid_from_str("ab.\ncd.\n...");
+        crate::testutil::assert_grid_eq(&grid.shift(1, 1, '.'), "...\n.ab\n.cd");
+        crate::testutil::assert_grid_eq(&grid.shift(-1, 0, '_'), "b._\nd._\n.._");
+        crate::testutil::assert_grid_eq(&grid.shift(0, 3, '_'), "___\n___\n___");
+    }
+
+    #[test]
+    fn grid_pad_with() {
+        use crate::testutil::{assert_grid_eq, grid_from_str};
+
+        let grid = grid_from_str("abc\ndef");
+        assert_grid_eq(
+            &grid.pad_with(1, PadMode::Constant('.')),
+            ".....\n.abc.\n.def.\n.....",
+        );
+        // first row mirrored into the top margin
+        assert_grid_eq(
+            &grid.pad_with(1, PadMode::Reflect),
+            "aabcc\naabcc\nddeff\nddeff",
+        );
+        assert_grid_eq(
+            &grid.pad_with(2, PadMode::Reflect).resize(7, 2, '.'),
+            "eddeffe\nbaabccb",
+        );
+        // opposite edge copied
+        assert_grid_eq(
+            &grid.pad_with(1, PadMode::Wrap),
+            "fdefd\ncabca\nfdefd\ncabca",
+        );
+    }
+
+    #[test]
+    fn grid_blit_clipped() {
+        use crate::testutil::{assert_grid_eq, grid_from_str};
+
+        let motif = grid_from_str("ab\ncd");
+        let mut grid = Grid::<char>::new(3, 3, '.');
+        // fully off the right edge
+        grid.blit(&motif, 5, 2);
+        grid.blit(&motif, 3, 0);
+        assert_grid_eq(&grid, "...\n...\n...");
+        // partly off the bottom edge
+        grid.blit(&motif, 0, 2);
+        assert_grid_eq(&grid, "...\n...\nab.");
+        // fully off the bottom edge
+        grid.blit(&motif, 0, 3);
+        assert_grid_eq(&grid, "...\n...\nab.");
+    }
+
+    #[test]
+    fn grid_blit_transformed() {
+        use crate::testutil::{assert_grid_eq, grid_from_str};
+
+        let motif = grid_from_str("abc\ndef");
+        let mut grid = Grid::<char>::new(5, 5, '.');
+        grid.blit_transformed(&motif, 1, 1, Transform::Rotate90);
+        assert_grid_eq(&grid, ".....\n.da..\n.eb..\n.fc..\n.....");
+
+        // clipped at the bottom-right corner
+        grid.blit_transformed(&motif, 3, 4, Transform::Rotate180);
+        assert_grid_eq(&grid, ".....\n.da..\n.eb..\n.fc..\n...fe");
+
+        assert_grid_eq(&motif.transformed(Transform::Rotate270), "cf\nbe\nad");
+        assert_grid_eq(&motif.transformed(Transform::FlipH), "cba\nfed");
+        assert_grid_eq(&motif.transformed(Transform::FlipV), "def\nabc");
+        assert_grid_eq(&motif.transformed(Transform::Rotate0), "abc\ndef");
+    }
+
+    #[test]
+    fn grid_adjacent_equal_pairs() {
+        use crate::testutil::grid_from_str;
+
+        // horizontal: aa, bb, aa ; vertical: a/a (x=0), b/b (x=2)
+        let grid = grid_from_str("aab\nabb\ncaa");
+        assert_eq!(grid.adjacent_equal_pairs(), 5);
+
+        assert_eq!(Grid::<u8>::new(3, 2, 0).adjacent_equal_pairs(), 7);
+        assert_eq!(Grid::<u8>::new(1, 1, 0).adjacent_equal_pairs(), 0);
+    }
+
+    #[test]
+    fn grid_neighbors4_signed() {
+        let grid = Grid::<u8>::new(3, 3, 0);
+        assert_eq!(
+            grid.neighbors4_signed(0, 0),
+            vec![(1, 0), (-1, 0), (0, 1), (0, -1)]
+        );
+        assert_eq!(
+            grid.neighbors4_signed(2, 1),
+            vec![(3, 1), (1, 1), (2, 2), (2, 0)]
+        );
+    }
+
     #[test]
     fn grid_braille_pattern() {
         assert_ne!(u8_to_braille(0), ' '); // we do NOT expect a 0x20 space
@@ -0,0 +1,100 @@
+//! Parsing helpers for the common input line formats.
+
+use std::str::FromStr;
+
+/// Extract all the signed integers of a line, in order,
+/// ignoring any other character ('-' is only a sign when
+/// directly followed by a digit).
+fn extract_signed_integers(line: &str) -> Vec<isize> {
+    let mut values = Vec::<isize>::new();
+    let mut start: Option<usize> = None;
+    let bytes = line.as_bytes();
+    for (i, c) in bytes.iter().enumerate() {
+        if c.is_ascii_digit() {
+            start.get_or_insert(i);
+            continue;
+        }
+        if let Some(s) = start.take() {
+            values.push(isize::from_str(&line[s..i]).unwrap());
+        }
+        if *c == b'-' && bytes.get(i + 1).is_some_and(|d| d.is_ascii_digit()) {
+            start = Some(i);
+        }
+    }
+    if let Some(s) = start {
+        values.push(isize::from_str(&line[s..]).unwrap());
+    }
+    values
+}
+
+/// Extract all the (x,y) integer pairs from a line, whatever
+/// the separators and labels around them, for example
+/// `p=27,64 v=24,-1` or `Button A: X+94, Y+34`.
+/// Panics if the line contains an odd number of integers.
+pub fn parse_signed_pairs(line: &str) -> Vec<(isize, isize)> {
+    let values = extract_signed_integers(line);
+    if !values.len().is_multiple_of(2) {
+        panic!("Odd number of integers in line \"{line}\", can't make pairs");
+    }
+    values.chunks_exact(2).map(|p| (p[0], p[1])).collect()
+}
+
+/// Parse all the whitespace-separated values of a line,
+/// whatever the number or kind of whitespaces between them.
+/// Panics on a value that can't be parsed.
+pub fn parse_row_ints<T: FromStr>(line: &str) -> Vec<T> {
+    line.split_whitespace()
+        .map(|v| {
+            T::from_str(v).unwrap_or_else(|_| panic!("Invalid value \"{v}\" in line \"{line}\""))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn signed_pairs_robots() {
+        assert_eq!(
+            parse_signed_pairs("p=27,64 v=24,1"),
+            vec![(27, 64), (24, 1)]
+        );
+        assert_eq!(
+            parse_signed_pairs("p=0,4 v=-3,-33"),
+            vec![(0, 4), (-3, -33)]
+        );
+    }
+
+    #[test]
+    fn signed_pairs_labels() {
+        assert_eq!(parse_signed_pairs("Button A: X+94, Y+34"), vec![(94, 34)]);
+        assert_eq!(
+            parse_signed_pairs("Prize: X=8400, Y=5400"),
+            vec![(8400, 5400)]
+        );
+        assert_eq!(parse_signed_pairs(""), vec![]);
+        // a dash not followed by a digit is not a sign
+        assert_eq!(parse_signed_pairs("a-b 1 - 2"), vec![(1, 2)]);
+        assert_eq!(parse_signed_pairs("3-4"), vec![(3, -4)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Odd number of integers")]
+    fn signed_pairs_odd() {
+        parse_signed_pairs("1,2,3");
+    }
+
+    #[test]
+    fn row_ints_whitespace() {
+        assert_eq!(parse_row_ints::<u32>("1  2\t3"), vec![1, 2, 3]);
+        assert_eq!(parse_row_ints::<i32>(" 3   -4 \n"), vec![3, -4]);
+        assert_eq!(parse_row_ints::<usize>(""), vec![]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid value \"x\"")]
+    fn row_ints_invalid() {
+        parse_row_ints::<u32>("1 x 3");
+    }
+}
@@ -3,6 +3,18 @@
 //! or methods.
 
 pub mod args;
+pub mod cache;
 pub mod colors;
 pub mod dijkstra;
+pub mod direction;
+pub mod graph;
 pub mod grid;
+pub mod input;
+pub mod logic;
+pub mod parse;
+pub mod search;
+pub mod solution;
+pub mod strings;
+pub mod testutil;
+pub mod util;
+pub mod vm;
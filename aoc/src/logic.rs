@@ -0,0 +1,189 @@
+//! Logic gates circuits, and analysis of their wiring.
+
+use std::collections::{HashMap, HashSet};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LogicalOp {
+    And,
+    Or,
+    Xor,
+}
+
+impl LogicalOp {
+    pub fn compute(&self, in1: u8, in2: u8) -> u8 {
+        match self {
+            LogicalOp::And => in1 & in2,
+            LogicalOp::Or => in1 | in2,
+            LogicalOp::Xor => in1 ^ in2,
+        }
+    }
+}
+
+/// A 2-inputs gate between named wires.
+#[derive(Clone, Debug)]
+pub struct Gate {
+    pub in1: String,
+    pub in2: String,
+    pub out: String,
+    pub op: LogicalOp,
+}
+
+impl Gate {
+    fn has_input_wires(&self) -> bool {
+        is_input_wire(&self.in1) && is_input_wire(&self.in2)
+    }
+
+    // Only the very first bit of a ripple-carry adder is a half-adder
+    // and doesn't follow the wiring of the others.
+    fn is_first_bit(&self) -> bool {
+        [&self.in1, &self.in2]
+            .iter()
+            .all(|w| *w == "x00" || *w == "y00")
+    }
+}
+
+fn is_input_wire(w: &str) -> bool {
+    w.starts_with('x') || w.starts_with('y')
+}
+
+/// Check a circuit supposed to be a ripple-carry adder of the
+/// inputs xNN and yNN into the outputs zNN, where each bit is
+/// a canonical full-adder:
+/// ```text
+/// xN XOR yN -> aN          (input-XOR)
+/// xN AND yN -> bN          (carry-AND)
+/// aN XOR cN-1 -> zN        (sum-XOR)
+/// aN AND cN-1 -> dN        (carry-AND)
+/// bN OR dN -> cN           (carry-OR)
+/// ```
+/// The names of the intermediate wires don't matter, only the
+/// role of each gate relatively to the others.
+/// Returns the (sorted) names of the output wires of all the
+/// gates that violate this wiring, which is where the outputs
+/// have been swapped.
+pub fn check_full_adder(gates: &[Gate]) -> Vec<String> {
+    // The last output bit is the final carry, coming from an OR.
+    let last_z = gates
+        .iter()
+        .filter(|g| g.out.starts_with('z'))
+        .map(|g| g.out.clone())
+        .max()
+        .unwrap_or_default();
+
+    // For each wire, the list of gate ops it is an input of.
+    let mut consumers = HashMap::<&str, Vec<&LogicalOp>>::new();
+    for g in gates {
+        consumers.entry(&g.in1).or_default().push(&g.op);
+        consumers.entry(&g.in2).or_default().push(&g.op);
+    }
+    let feeds_into = |wire: &str, op: LogicalOp| -> bool {
+        consumers
+            .get(wire)
+            .is_some_and(|ops| ops.iter().any(|o| **o == op))
+    };
+
+    let mut suspicious = HashSet::<String>::new();
+    for g in gates {
+        let bad = if g.out == last_z {
+            // the final carry-OR directly drives the last output
+            g.op != LogicalOp::Or
+        } else if g.out.starts_with('z') {
+            // other outputs are only driven by sum-XOR
+            g.op != LogicalOp::Xor || (g.has_input_wires() && !g.is_first_bit())
+        } else {
+            match g.op {
+                // input-XOR always feed the sum-XOR of the same bit
+                LogicalOp::Xor if g.has_input_wires() => !feeds_into(&g.out, LogicalOp::Xor),
+                // sum-XOR always drive an output
+                LogicalOp::Xor => true,
+                // carry-AND always feed the carry-OR
+                LogicalOp::And => !g.is_first_bit() && !feeds_into(&g.out, LogicalOp::Or),
+                // carry-OR always feed the sum-XOR of the next bit
+                LogicalOp::Or => !feeds_into(&g.out, LogicalOp::Xor),
+            }
+        };
+        if bad {
+            suspicious.insert(g.out.clone());
+        }
+    }
+
+    let mut suspicious: Vec<String> = suspicious.into_iter().collect();
+    suspicious.sort();
+    suspicious
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn gate(in1: &str, op: LogicalOp, in2: &str, out: &str) -> Gate {
+        Gate {
+            in1: in1.to_string(),
+            in2: in2.to_string(),
+            out: out.to_string(),
+            op,
+        }
+    }
+
+    // Generate a correct ripple-carry adder of "bits" bits.
+    fn build_adder(bits: usize) -> Vec<Gate> {
+        let mut gates = vec![
+            gate("x00", LogicalOp::Xor, "y00", "z00"),
+            gate("x00", LogicalOp::And, "y00", "c00"),
+        ];
+        for n in 1..bits {
+            let x = format!("x{n:02}");
+            let y = format!("y{n:02}");
+            let a = format!("a{n:02}");
+            let b = format!("b{n:02}");
+            let d = format!("d{n:02}");
+            let z = format!("z{n:02}");
+            let c_prev = format!("c{:02}", n - 1);
+            let c = if n == bits - 1 {
+                format!("z{bits:02}")
+            } else {
+                format!("c{n:02}")
+            };
+            gates.push(gate(&x, LogicalOp::Xor, &y, &a));
+            gates.push(gate(&y, LogicalOp::And, &x, &b));
+            gates.push(gate(&c_prev, LogicalOp::Xor, &a, &z));
+            gates.push(gate(&a, LogicalOp::And, &c_prev, &d));
+            gates.push(gate(&b, LogicalOp::Or, &d, &c));
+        }
+        gates
+    }
+
+    fn swap_outputs(gates: &mut [Gate], w1: &str, w2: &str) {
+        for g in gates.iter_mut() {
+            if g.out == w1 {
+                g.out = w2.to_string();
+            } else if g.out == w2 {
+                g.out = w1.to_string();
+            }
+        }
+    }
+
+    #[test]
+    fn adder_no_swap() {
+        let gates = build_adder(8);
+        assert!(check_full_adder(&gates).is_empty());
+    }
+
+    #[test]
+    fn adder_one_swap() {
+        let mut gates = build_adder(8);
+        // sum-XOR output exchanged with the carry-OR
+        swap_outputs(&mut gates, "z03", "c03");
+        assert_eq!(check_full_adder(&gates), vec!["c03", "z03"]);
+    }
+
+    #[test]
+    fn adder_two_swaps() {
+        let mut gates = build_adder(8);
+        // input-XOR exchanged with input-AND
+        swap_outputs(&mut gates, "a02", "b02");
+        // sum-XOR exchanged with the second carry-AND
+        swap_outputs(&mut gates, "z05", "d05");
+        assert_eq!(check_full_adder(&gates), vec!["a02", "b02", "d05", "z05"]);
+    }
+}
@@ -0,0 +1,178 @@
+//! Parsing of whole inputs into the common structures.
+
+use crate::grid::{Grid, GridBuilder};
+use std::collections::HashMap;
+
+/// Coordinates of each marker character found in a map.
+pub type Markers = HashMap<char, Vec<(usize, usize)>>;
+
+/// Parse the lines of a map into a char grid, kept untouched, and
+/// also return the coordinates of each of the requested marker
+/// characters (like 'S' and 'E') in reading order.
+/// Every requested marker has an entry, empty if it was not found.
+pub fn parse_grid_with_markers(lines: &[String], markers: &[char]) -> (Grid<char>, Markers) {
+    let mut gb = GridBuilder::<char>::new();
+    let mut positions: Markers = markers.iter().map(|m| (*m, Vec::new())).collect();
+
+    for (y, line) in lines.iter().enumerate() {
+        let row: Vec<char> = line.chars().collect();
+        for (x, c) in row.iter().enumerate() {
+            if let Some(found) = positions.get_mut(c) {
+                found.push((x, y));
+            }
+        }
+        gb.append_line(&row);
+    }
+
+    (gb.to_grid(), positions)
+}
+
+/// Split each line of a block of "key<sep>value" lines into
+/// its (key, value) pair, in order.
+/// Panics on a line without the separator.
+pub fn parse_kv_block(lines: &[&str], sep: &str) -> Vec<(String, String)> {
+    lines
+        .iter()
+        .map(|line| {
+            let (k, v) = line
+                .split_once(sep)
+                .unwrap_or_else(|| panic!("Missing \"{sep}\" in line \"{line}\""));
+            (k.to_string(), v.to_string())
+        })
+        .collect()
+}
+
+/// Split each "a<sep>b" line of an edge list (like day 23 "kh-tc")
+/// into its owned (a, b) pair, in order.
+/// Panics on a line without the separator.
+pub fn parse_edges(lines: &[String], sep: char) -> Vec<(String, String)> {
+    lines
+        .iter()
+        .map(|line| {
+            let (a, b) = line
+                .split_once(sep)
+                .unwrap_or_else(|| panic!("Missing edge separator '{sep}' in line \"{line}\""));
+            (a.to_string(), b.to_string())
+        })
+        .collect()
+}
+
+/// Differences between each consecutive values of a sequence
+/// (one less than the values).
+pub fn deltas(v: &[i64]) -> Vec<i64> {
+    v.windows(2).map(|w| w[1] - w[0]).collect()
+}
+
+/// True if every step of the sequence, v[i+1] - v[i], is within
+/// lo..=hi. With a negative range this checks a decreasing sequence.
+pub fn all_increasing_within(v: &[i64], lo: i64, hi: i64) -> bool {
+    v.windows(2).all(|w| (lo..=hi).contains(&(w[1] - w[0])))
+}
+
+/// Split an input made of a map block, a blank line, then a block
+/// of moves possibly wrapped on several lines (like day 15).
+/// Returns the map lines and all the moves concatenated.
+pub fn parse_map_and_moves(lines: &[String]) -> (Vec<String>, String) {
+    let mut blocks = lines.split(|l| l.is_empty());
+    let map = blocks.next().unwrap_or_default().to_vec();
+    let moves = blocks.flatten().map(|l| l.as_str()).collect();
+    (map, moves)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn grid_with_markers_maze() {
+        let lines: Vec<String> = ["#####", "#S..#", "#.#E#", "#...#", "#####"]
+            .iter()
+            .map(|l| l.to_string())
+            .collect();
+        let (grid, markers) = parse_grid_with_markers(&lines, &['S', 'E', 'X']);
+
+        assert_eq!((grid.width, grid.height), (5, 5));
+        assert_eq!(grid.get(1, 1), 'S');
+        assert_eq!(markers[&'S'], vec![(1, 1)]);
+        assert_eq!(markers[&'E'], vec![(3, 2)]);
+        assert_eq!(markers[&'X'], vec![]);
+        assert!(!markers.contains_key(&'#'));
+    }
+
+    #[test]
+    fn kv_block() {
+        let pairs = parse_kv_block(&["Register A: 729", "x00: 1", "Program: 0,1,5"], ": ");
+        assert_eq!(
+            pairs,
+            vec![
+                ("Register A".to_string(), "729".to_string()),
+                ("x00".to_string(), "1".to_string()),
+                ("Program".to_string(), "0,1,5".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing \": \" in line \"x00 1\"")]
+    fn kv_block_no_separator() {
+        parse_kv_block(&["x00 1"], ": ");
+    }
+
+    #[test]
+    fn edges() {
+        let lines: Vec<String> = ["kh-tc", "qp-kh", "de-cg"].map(String::from).to_vec();
+        assert_eq!(
+            parse_edges(&lines, '-'),
+            vec![
+                ("kh".to_string(), "tc".to_string()),
+                ("qp".to_string(), "kh".to_string()),
+                ("de".to_string(), "cg".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Missing edge separator '-' in line \"kh tc\"")]
+    fn edges_no_separator() {
+        parse_edges(&["kh-tc".to_string(), "kh tc".to_string()], '-');
+    }
+
+    #[test]
+    fn sequence_deltas() {
+        assert_eq!(deltas(&[1, 3, 6, 4]), vec![2, 3, -2]);
+        assert_eq!(deltas(&[5]), vec![]);
+    }
+
+    #[test]
+    fn sequence_within() {
+        // day 2 sample reports: safe if all increasing or all
+        // decreasing by 1 to 3
+        let reports: [&[i64]; 6] = [
+            &[7, 6, 4, 2, 1],
+            &[1, 2, 7, 8, 9],
+            &[9, 7, 6, 2, 1],
+            &[1, 3, 2, 4, 5],
+            &[8, 6, 4, 4, 1],
+            &[1, 3, 6, 7, 9],
+        ];
+        let safe: Vec<bool> = reports
+            .iter()
+            .map(|r| all_increasing_within(r, 1, 3) || all_increasing_within(r, -3, -1))
+            .collect();
+        assert_eq!(safe, vec![true, false, false, false, false, true]);
+        assert!(all_increasing_within(&[1, 3, 6, 7, 9], 1, 3));
+        assert!(!all_increasing_within(&[7, 6, 4, 2, 1], 1, 3));
+    }
+
+    #[test]
+    fn map_and_moves() {
+        let input = "#####\n#@.O#\n#####\n\n<>^\nvv<\n>";
+        let lines: Vec<String> = input.lines().map(String::from).collect();
+        let (map, moves) = parse_map_and_moves(&lines);
+        assert_eq!(map, vec!["#####", "#@.O#", "#####"]);
+        assert_eq!(moves, "<>^vv<>");
+
+        let lines = vec!["#@#".to_string()];
+        assert_eq!(parse_map_and_moves(&lines), (lines.clone(), String::new()));
+    }
+}
@@ -0,0 +1,87 @@
+//! Generic search helpers over integer ranges.
+
+/// Bisect the range (lo,hi] to find the smallest value for which
+/// is_bad() is true.
+/// Assumes is_bad() is monotonic: false up to some value, then always true.
+/// lo is expected to be "good" and hi "bad"; they are never tested.
+/// Returns hi if every value in the range is good but hi.
+pub fn bisect(mut lo: usize, mut hi: usize, mut is_bad: impl FnMut(usize) -> bool) -> usize {
+    if hi <= lo {
+        panic!("bisect: empty range ({lo},{hi}]");
+    }
+
+    while hi > lo + 1 {
+        let mid = lo + (hi - lo) / 2;
+        if is_bad(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+
+    hi
+}
+
+/// Find the last value of the range [lo,hi) for which pred() is true.
+/// Assumes pred() is monotonic: true up to some value, then always false.
+/// Returns None if pred() is false for the whole range (or if it is empty).
+pub fn bisect_last_true(
+    lo: usize,
+    hi: usize,
+    mut pred: impl FnMut(usize) -> bool,
+) -> Option<usize> {
+    if hi <= lo || !pred(lo) {
+        return None;
+    }
+    if hi == lo + 1 {
+        return Some(lo);
+    }
+
+    // lo is known true, and hi outside of the range is considered false.
+    Some(bisect(lo, hi, |v| !pred(v)) - 1)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bisect_threshold() {
+        for threshold in 1..=20 {
+            assert_eq!(bisect(0, 20, |v| v >= threshold), threshold);
+        }
+        assert_eq!(bisect(5, 6, |_| panic!("nothing to test")), 6);
+        assert_eq!(bisect(1000, 1_000_000, |v| v * v > 2_000_000_000), 44722);
+    }
+
+    #[test]
+    fn bisect_tests_few_values() {
+        let mut tested = Vec::new();
+        let found = bisect(0, 1024, |v| {
+            tested.push(v);
+            v >= 700
+        });
+        assert_eq!(found, 700);
+        assert_eq!(tested.len(), 10);
+        assert!(tested.iter().all(|v| *v > 0 && *v < 1024));
+    }
+
+    #[test]
+    fn bisect_last_true_boundary() {
+        for last in 3..10 {
+            assert_eq!(bisect_last_true(3, 10, |v| v <= last), Some(last));
+        }
+        // true for the entire range
+        assert_eq!(bisect_last_true(3, 10, |_| true), Some(9));
+        // false for the entire range
+        assert_eq!(bisect_last_true(3, 10, |_| false), None);
+        assert_eq!(bisect_last_true(3, 3, |_| true), None);
+        assert_eq!(bisect_last_true(3, 4, |_| true), Some(3));
+    }
+
+    #[test]
+    #[should_panic]
+    fn bisect_empty_range() {
+        bisect(3, 3, |_| true);
+    }
+}
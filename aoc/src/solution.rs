@@ -0,0 +1,56 @@
+//! Common result of a day solution, so that days can
+//! return their answers from a solve() function
+//! instead of printing them directly, and be tested on samples.
+
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Answer {
+    pub part1: String,
+    /// None if part 2 is not solved (yet)
+    pub part2: Option<String>,
+}
+
+impl Answer {
+    pub fn new(part1: impl fmt::Display, part2: impl fmt::Display) -> Self {
+        Answer {
+            part1: part1.to_string(),
+            part2: Some(part2.to_string()),
+        }
+    }
+
+    pub fn part1_only(part1: impl fmt::Display) -> Self {
+        Answer {
+            part1: part1.to_string(),
+            part2: None,
+        }
+    }
+}
+
+/// Print the answers in the usual "Part N = " format, one per line.
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Part 1 = {}", self.part1)?;
+        if let Some(part2) = &self.part2 {
+            writeln!(f, "Part 2 = {}", part2)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn answer_display() {
+        let a = Answer::new(11, "abc");
+        assert_eq!(a.part1, "11");
+        assert_eq!(a.part2, Some("abc".to_string()));
+        assert_eq!(a.to_string(), "Part 1 = 11\nPart 2 = abc\n");
+
+        let a = Answer::part1_only(42usize);
+        assert_eq!(a.part2, None);
+        assert_eq!(a.to_string(), "Part 1 = 42\n");
+    }
+}
@@ -0,0 +1,87 @@
+//! Helpers on strings seen as sequences of smaller pieces.
+
+use crate::cache::Memo;
+
+/// Number of different ways to concatenate elements of "pieces"
+/// (each usable any number of times) into exactly "target".
+/// Memoized on the remaining suffix, so that disjoint parts of the
+/// target are not explored again when backtracking (like day 19).
+pub fn tiling_count(target: &str, pieces: &[&str]) -> usize {
+    tiling_count_memo(target, pieces, &mut Memo::new())
+}
+
+// The memo only holds slices of the original target, so it can
+// reference them instead of owning copies.
+fn tiling_count_memo<'a>(
+    target: &'a str,
+    pieces: &[&str],
+    memo: &mut Memo<&'a str, usize>,
+) -> usize {
+    if target.is_empty() {
+        // Leaf found, the stack reaching it equals 1 possible
+        // combination
+        return 1;
+    }
+
+    memo.get_or_compute(target, |memo| {
+        pieces
+            .iter()
+            .filter_map(|p| target.strip_prefix(p))
+            .map(|rest| tiling_count_memo(rest, pieces, memo))
+            .sum()
+    })
+}
+
+/// True if "target" is a concatenation of elements of "pieces".
+/// Same as tiling_count() > 0 but stops at the first tiling found.
+pub fn is_tileable(target: &str, pieces: &[&str]) -> bool {
+    is_tileable_memo(target, pieces, &mut Memo::new())
+}
+
+fn is_tileable_memo<'a>(target: &'a str, pieces: &[&str], memo: &mut Memo<&'a str, bool>) -> bool {
+    if target.is_empty() {
+        return true;
+    }
+
+    memo.get_or_compute(target, |memo| {
+        pieces
+            .iter()
+            .filter_map(|p| target.strip_prefix(p))
+            .any(|rest| is_tileable_memo(rest, pieces, memo))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tiling_small() {
+        let pieces = ["r", "wr", "b"];
+        assert_eq!(tiling_count("brwrr", &pieces), 1);
+        assert!(is_tileable("brwrr", &pieces));
+        assert_eq!(tiling_count("bwrr", &pieces), 1);
+        assert_eq!(tiling_count("bww", &pieces), 0);
+        assert!(!is_tileable("bww", &pieces));
+        assert_eq!(tiling_count("", &pieces), 1);
+    }
+
+    #[test]
+    fn tiling_day19_sample() {
+        let pieces = ["r", "wr", "b", "g", "bwu", "rb", "gb", "br"];
+        let designs = [
+            ("brwrr", 2),
+            ("bggr", 1),
+            ("gbbr", 4),
+            ("rrbgbr", 6),
+            ("ubwu", 0),
+            ("bwurrg", 1),
+            ("brgr", 2),
+            ("bbrgwb", 0),
+        ];
+        for (design, count) in designs {
+            assert_eq!(tiling_count(design, &pieces), count, "{design}");
+            assert_eq!(is_tileable(design, &pieces), count > 0, "{design}");
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! Helpers for unit tests of grid-based solutions, and
+//! deterministic pseudo-random test inputs.
+
+use crate::grid::{Grid, GridBuilder};
+
+/// Parse a multi-line string into a char grid.
+/// Empty lines are skipped so the pattern can be written
+/// with surrounding newlines; other whitespace is kept as-is
+/// as it is often significant in a rendered grid.
+pub fn grid_from_str(s: &str) -> Grid<char> {
+    let mut gb = GridBuilder::<char>::new();
+    for line in s.lines().filter(|l| !l.is_empty()) {
+        let row: Vec<char> = line.chars().collect();
+        gb.append_line(&row);
+    }
+    gb.to_grid()
+}
+
+/// Assert that a char grid is equal to the expected pattern
+/// given as a multi-line string.
+/// On failure, both grids are printed and the panic message
+/// lists the coordinates of every differing cell.
+pub fn assert_grid_eq(actual: &Grid<char>, expected: &str) {
+    let expected = grid_from_str(expected);
+    if (actual.width, actual.height) != (expected.width, expected.height) {
+        panic!(
+            "grid dimension mismatch: actual [{},{}], expected [{},{}]",
+            actual.width, actual.height, expected.width, expected.height
+        );
+    }
+
+    let mut diffs = Vec::<String>::new();
+    for y in 0..actual.height {
+        for x in 0..actual.width {
+            let a = actual.get(x, y);
+            let e = expected.get(x, y);
+            if a != e {
+                diffs.push(format!("({x},{y}): expected '{e}', found '{a}'"));
+            }
+        }
+    }
+
+    if !diffs.is_empty() {
+        eprintln!("actual:");
+        actual.pretty_print_lambda_char(&|c| c);
+        eprintln!("expected:");
+        expected.pretty_print_lambda_char(&|c| c);
+        panic!(
+            "grids differ in {} cell(s):\n{}",
+            diffs.len(),
+            diffs.join("\n")
+        );
+    }
+}
+
+/// Minimal 64-bit linear congruential generator, to build
+/// reproducible pseudo-random inputs for tests without a crate.
+/// Yields the upper 31 bits of the state, the low bits of an LCG
+/// being far from random.
+pub struct Lcg {
+    state: u64,
+}
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Lcg { state: seed }
+    }
+}
+
+impl Iterator for Lcg {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        self.state = self
+            .state
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        Some(self.state >> 33)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn grid_eq_identical() {
+        let grid = grid_from_str("ab.\n.cd\n");
+        assert_eq!((grid.width, grid.height), (3, 2));
+        assert_grid_eq(&grid, "\nab.\n.cd\n");
+    }
+
+    #[test]
+    #[should_panic(expected = "(2,1): expected 'x', found 'd'")]
+    fn grid_eq_reports_coordinate() {
+        let grid = grid_from_str("ab.\n.cd");
+        assert_grid_eq(&grid, "ab.\n.cx");
+    }
+
+    #[test]
+    #[should_panic(expected = "dimension mismatch")]
+    fn grid_eq_reports_dimensions() {
+        let grid = grid_from_str("ab.\n.cd");
+        assert_grid_eq(&grid, "ab\n.c");
+    }
+
+    #[test]
+    fn lcg_reproducible() {
+        let a: Vec<u64> = Lcg::new(12345).take(100).collect();
+        let b: Vec<u64> = Lcg::new(12345).take(100).collect();
+        assert_eq!(a, b);
+        assert!(a.iter().all(|v| *v < 1 << 31));
+        // not stuck on a constant or a short cycle
+        let mut distinct = a.clone();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(distinct.len(), 100);
+        assert_ne!(Lcg::new(1).next(), Lcg::new(2).next());
+    }
+}
@@ -0,0 +1,75 @@
+//! Small generic helpers that don't belong to a specific structure.
+
+/// Return the number of positions where the two slices differ.
+/// Panics if they are not of the same length.
+pub fn hamming_distance<T: PartialEq>(a: &[T], b: &[T]) -> usize {
+    if a.len() != b.len() {
+        panic!("Can't compare slices of different len");
+    }
+
+    a.iter().zip(b).filter(|(x, y)| x != y).count()
+}
+
+/// Encode a small tuple of values as a single index, as the digits
+/// of a number in the given base (first value is the most significant).
+/// offset is added to each value to shift negatives into 0..base.
+/// Panics if a shifted value is not a valid digit.
+pub fn encode_base(values: &[isize], offset: isize, base: usize) -> usize {
+    values.iter().fold(0, |index, v| {
+        let digit = v + offset;
+        if digit < 0 || digit as usize >= base {
+            panic!("value {v} out of range for offset {offset} and base {base}");
+        }
+        index * base + digit as usize
+    })
+}
+
+/// Reverse of encode_base(), for a tuple of len values.
+pub fn decode_base(mut index: usize, len: usize, offset: isize, base: usize) -> Vec<isize> {
+    let mut values = vec![0; len];
+    for v in values.iter_mut().rev() {
+        *v = (index % base) as isize - offset;
+        index /= base;
+    }
+    values
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn util_hamming_distance() {
+        assert_eq!(hamming_distance(&[1, 2, 3], &[1, 2, 3]), 0);
+        assert_eq!(hamming_distance::<u8>(&[], &[]), 0);
+        assert_eq!(hamming_distance(&[1, 2, 3], &[1, 0, 0]), 2);
+        assert_eq!(hamming_distance(&[true, false], &[false, true]), 2);
+        assert_eq!(hamming_distance(b"karolin", b"kathrin"), 3);
+    }
+
+    #[test]
+    fn util_encode_base() {
+        let index = encode_base(&[-9, 0, 9, 3], 9, 19);
+        assert_eq!(index, 9 * 19 * 19 + 18 * 19 + 12);
+        assert_eq!(decode_base(index, 4, 9, 19), vec![-9, 0, 9, 3]);
+
+        assert_eq!(encode_base(&[-9, -9, -9, -9], 9, 19), 0);
+        assert_eq!(encode_base(&[9, 9, 9, 9], 9, 19), 19 * 19 * 19 * 19 - 1);
+        assert_eq!(encode_base(&[1, 0, 1], 0, 2), 0b101);
+        for index in [0, 1, 42, 6859, 130320] {
+            assert_eq!(encode_base(&decode_base(index, 4, 9, 19), 9, 19), index);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn util_encode_base_out_of_range() {
+        encode_base(&[10], 9, 19);
+    }
+
+    #[test]
+    #[should_panic(expected = "different len")]
+    fn util_hamming_distance_mismatch() {
+        hamming_distance(&[1, 2, 3], &[1, 2]);
+    }
+}
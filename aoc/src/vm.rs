@@ -0,0 +1,461 @@
+//! The 3-bit computer of day 17: a register machine
+//! with a program of 3-bit instructions.
+
+use crate::parse::parse_kv_block;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// The 3-bit computer: 3 registers and a program of 3-bit
+/// instructions and operands.
+#[derive(Clone, Debug)]
+pub struct Machine {
+    instruction_ptr: usize,
+    pub register_a: usize,
+    pub register_b: usize,
+    pub register_c: usize,
+    pub program: Vec<u8>,
+    output: Vec<u8>,
+}
+
+/// Operand that is either a literal 0..=3 or a register (4..=6).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ComboOperand(pub u8);
+/// Operand used directly as its value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LiteralOperand(pub u8);
+
+/// Each possible machine instruction with their embedded operand type.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    Adv(ComboOperand),
+    Bxl(LiteralOperand),
+    Bst(ComboOperand),
+    Jnz(LiteralOperand),
+    Bxc,
+    Out(ComboOperand),
+    Bdv(ComboOperand),
+    Cdv(ComboOperand),
+}
+
+use Instruction::*;
+
+impl Machine {
+    /// A machine with all registers at 0, ready to run the program.
+    pub fn with_program(program: &[u8]) -> Self {
+        Machine {
+            instruction_ptr: 0,
+            register_a: 0,
+            register_b: 0,
+            register_c: 0,
+            program: program.to_vec(),
+            output: Vec::<u8>::new(),
+        }
+    }
+
+    /// Run the program from its current state/IP until it halts.
+    pub fn run(&mut self) {
+        while self.execute_one_step() {}
+    }
+
+    /// Same as run(), but on_step is called before each step with
+    /// the machine state and the instruction about to be executed.
+    pub fn run_with_trace(&mut self, on_step: &mut dyn FnMut(&Machine, &Instruction)) {
+        while self.instruction_ptr < self.program.len() {
+            on_step(self, &self.decode_current_instruction());
+            self.execute_one_step();
+        }
+    }
+
+    /// Run the program. If the output is different
+    /// than the program code, stops and return false.
+    /// If it halts and output == program, return true.
+    pub fn run_until_halt_or_non_quine(&mut self) -> bool {
+        self.run_until_halt_or_non_quine_or_outlen(self.program.len() + 1)
+    }
+
+    /// Run the program. If the output is different
+    /// than the program code, stops and return false.
+    /// If it halts, or the output length reached the
+    /// specified size, and output == program, return true.
+    pub fn run_until_halt_or_non_quine_or_outlen(&mut self, maxlen: usize) -> bool {
+        self.run_while_matching(None, maxlen)
+            && (self.output.len() == maxlen || self.output.len() == self.program.len())
+    }
+
+    /// Run the program until it halts, as long as its output is a
+    /// prefix of "target". Stops and returns false as soon as the
+    /// output differs from, or gets longer than, the target.
+    pub fn outputs_prefix_of(&mut self, target: &[u8]) -> bool {
+        self.run_while_matching(Some(target), usize::MAX)
+    }
+
+    // Common part of the output checks: run while the output matches the
+    // start of target (or of the program code itself if None).
+    // Returns false on the first difference, true when the machine
+    // halts or the output reached maxlen values.
+    fn run_while_matching(&mut self, target: Option<&[u8]>, maxlen: usize) -> bool {
+        // Compare only new "out" elements (no need
+        // to compare the full array every time)
+        let mut checked_len = 0;
+        loop {
+            let halted = !self.execute_one_step();
+            let out_len = self.output.len();
+            if out_len > checked_len {
+                let expected = match target {
+                    Some(t) => t.get(out_len - 1),
+                    None => self.program.get(out_len - 1),
+                };
+                // latest element differs, or output longer than target
+                if expected != Some(&self.output[out_len - 1]) {
+                    return false;
+                }
+                checked_len = out_len;
+            }
+            if out_len == maxlen || halted {
+                return true;
+            }
+        }
+    }
+
+    /// Reboots the machine with a specific starting register value.
+    pub fn reset_with_register(&mut self, a: usize) {
+        self.instruction_ptr = 0;
+        self.register_a = a;
+        self.register_b = 0;
+        self.register_c = 0;
+        self.output.truncate(0);
+    }
+
+    /// Run the program for exactly one instruction
+    /// (at current IP).
+    /// Returns true if it executed,
+    /// false if it is now halted.
+    pub fn execute_one_step(&mut self) -> bool {
+        if self.instruction_ptr >= self.program.len() {
+            return false;
+        }
+
+        let ins = self.decode_current_instruction();
+        self.instruction_ptr += 2;
+
+        match ins {
+            // Div A by power of 2 (= bit shift), multiple register dest
+            Adv(d) | Bdv(d) | Cdv(d) => {
+                let res = self.register_a >> self.get_combo_value(d);
+                match ins {
+                    Adv(_) => self.register_a = res,
+                    Bdv(_) => self.register_b = res,
+                    Cdv(_) => self.register_c = res,
+                    // rustc should know that we can match only on the first ones ?
+                    _ => panic!("Impossible inner match"),
+                }
+            }
+            // bitwise xor
+            Bxl(x) => self.register_b ^= x.0 as usize,
+            // modulo 8
+            Bst(v) => self.register_b = self.get_combo_value(v) % 8,
+            // cond jump if A != 0
+            Jnz(p) => {
+                if self.register_a != 0 {
+                    self.instruction_ptr = p.0 as usize;
+                }
+            }
+            // Xor C into B
+            Bxc => self.register_b ^= self.register_c,
+            // out
+            Out(o) => self.output.push((self.get_combo_value(o) % 8) as u8),
+        }
+
+        true
+    }
+
+    /// Return the Instruction encoded at current IP
+    fn decode_current_instruction(&self) -> Instruction {
+        self.decode_instruction_at(self.instruction_ptr)
+    }
+
+    /// Return the Instruction encoded at any valid program offset
+    pub fn decode_instruction_at(&self, p: usize) -> Instruction {
+        let i = self.program[p];
+        let o = self.program[p + 1];
+
+        match i {
+            0 => Adv(ComboOperand(o)),
+            1 => Bxl(LiteralOperand(o)),
+            2 => Bst(ComboOperand(o)),
+            3 => Jnz(LiteralOperand(o)),
+            4 => Bxc,
+            5 => Out(ComboOperand(o)),
+            6 => Bdv(ComboOperand(o)),
+            7 => Cdv(ComboOperand(o)),
+            _ => panic!("Illegal instruction {i}"),
+        }
+    }
+
+    /// Make the necessary indirection from a combo operand
+    /// encoding into the real value/register value.
+    fn get_combo_value(&self, o: ComboOperand) -> usize {
+        match o.0 {
+            0..=3 => o.0.into(),
+            4 => self.register_a,
+            5 => self.register_b,
+            6 => self.register_c,
+            _ => panic!("Illegal combo operand {}", o.0),
+        }
+    }
+
+    /// The values output so far, each in 0..=7
+    pub fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    /// Format the output vector with coma separator
+    pub fn output_string(&self) -> String {
+        self.output
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    pub fn print_output(&self) {
+        println!("Program output:");
+        println!("{}", self.output_string());
+    }
+
+    fn get_combo_representation(o: ComboOperand) -> String {
+        match o.0 {
+            0..=3 => o.0.to_string(),
+            4 => String::from("A"),
+            5 => String::from("B"),
+            6 => String::from("C"),
+            _ => String::from("???"),
+        }
+    }
+
+    /// For debug: readable assembly, one line per instruction
+    pub fn assembly_lines(&self) -> Vec<String> {
+        (0..self.program.len())
+            .step_by(2)
+            .map(|k| {
+                let asm = match self.decode_instruction_at(k) {
+                    Adv(d) => format!("ADV {}", Self::get_combo_representation(d)),
+                    Bdv(d) => format!("BDV {}", Self::get_combo_representation(d)),
+                    Cdv(d) => format!("CDV {}", Self::get_combo_representation(d)),
+                    Bxl(x) => format!("BXL {}", x.0),
+                    Bst(v) => format!("BST {}", Self::get_combo_representation(v)),
+                    Jnz(p) => format!("JNZ {}", p.0),
+                    // Xor C into B
+                    Bxc => "BXC".to_string(),
+                    // out
+                    Out(o) => format!("OUT {} % 8", Self::get_combo_representation(o)),
+                };
+                format!("{:02}: {asm}", k)
+            })
+            .collect()
+    }
+
+    /// For debug: same as assembly_lines() but as pseudocode
+    pub fn pseudocode_lines(&self) -> Vec<String> {
+        (0..self.program.len())
+            .step_by(2)
+            .map(|k| {
+                let code = match self.decode_instruction_at(k) {
+                    Adv(d) => format!("A = A >> {}", Self::get_combo_representation(d)),
+                    Bdv(d) => format!("B = A >> {}", Self::get_combo_representation(d)),
+                    Cdv(d) => format!("C = A >> {}", Self::get_combo_representation(d)),
+                    Bxl(x) => format!("B = B xor {}", x.0),
+                    Bst(v) => format!("B = {} % 8", Self::get_combo_representation(v)),
+                    Jnz(p) => format!("If A != 0 JMP {}", p.0),
+                    Bxc => "B = B xor C".to_string(),
+                    // out
+                    Out(o) => format!("OUT {} % 8", Self::get_combo_representation(o)),
+                };
+                format!("{:02}: {code}", k)
+            })
+            .collect()
+    }
+
+    pub fn pretty_print_assembly(&self) {
+        for l in self.assembly_lines() {
+            eprintln!("{l}");
+        }
+    }
+
+    pub fn pretty_print_pseudocode(&self) {
+        for l in self.pseudocode_lines() {
+            eprintln!("{l}");
+        }
+    }
+}
+
+/// Parse a machine description:
+/// ```text
+/// Register A: 729
+/// Register B: 0
+/// Register C: 0
+///
+/// Program: 0,1,5,4,3,0
+/// ```
+/// Any line after the program is ignored.
+pub fn parse(input: &str) -> Machine {
+    // Anything after the program line is ignored
+    let lines: Vec<&str> = input.lines().filter(|l| !l.is_empty()).take(4).collect();
+    let values: HashMap<String, String> = parse_kv_block(&lines, ": ").into_iter().collect();
+    let value = |key: &str| {
+        values
+            .get(key)
+            .unwrap_or_else(|| panic!("Missing \"{key}\" in machine description"))
+    };
+    let register = |key: &str| usize::from_str(value(key)).unwrap();
+
+    let program = value("Program")
+        .split(',')
+        .map(|v| u8::from_str(v).unwrap())
+        .collect::<Vec<u8>>();
+
+    let mut machine = Machine::with_program(&program);
+    machine.register_a = register("Register A");
+    machine.register_b = register("Register B");
+    machine.register_c = register("Register C");
+    machine
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn vm_parse_and_run() {
+        let mut machine =
+            parse("Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4,3,0\n");
+        assert_eq!(machine.register_a, 729);
+        assert_eq!(machine.program, vec![0, 1, 5, 4, 3, 0]);
+        machine.run();
+        assert_eq!(machine.output_string(), "4,6,3,5,6,3,5,2,1,0");
+        assert_eq!(machine.register_a, 0);
+
+        // comments after the program are ignored
+        let machine =
+            parse("Register A: 1\nRegister B: 2\nRegister C: 3\n\nProgram: 5,5\n\n# A is 1\n");
+        assert_eq!(machine.register_c, 3);
+        assert_eq!(machine.program, vec![5, 5]);
+    }
+
+    // Run a program from the given registers, returns the
+    // final (A, B, C) and output.
+    fn run_program(
+        program: &[u8],
+        a: usize,
+        b: usize,
+        c: usize,
+    ) -> ((usize, usize, usize), Vec<u8>) {
+        let mut machine = Machine::with_program(program);
+        machine.register_a = a;
+        machine.register_b = b;
+        machine.register_c = c;
+        machine.run();
+        (
+            (machine.register_a, machine.register_b, machine.register_c),
+            machine.output().to_vec(),
+        )
+    }
+
+    #[test]
+    fn vm_opcodes() {
+        // ADV 2: A = A >> 2
+        assert_eq!(run_program(&[0, 2], 13, 0, 0).0, (3, 0, 0));
+        // BXL 5
+        assert_eq!(run_program(&[1, 5], 0, 3, 0).0, (0, 6, 0));
+        // BST A, OUT B
+        assert_eq!(run_program(&[2, 4, 5, 5], 29, 0, 0), ((29, 5, 0), vec![5]));
+        // JNZ 0 looping with ADV 1 until A is 0
+        assert_eq!(run_program(&[0, 1, 3, 0], 8, 0, 0).0, (0, 0, 0));
+        // BXC (operand ignored)
+        assert_eq!(run_program(&[4, 7], 0, 6, 3).0, (0, 5, 3));
+        // OUT 3 (literal), OUT C
+        assert_eq!(run_program(&[5, 3, 5, 6], 0, 0, 12).1, vec![3, 4]);
+        // BDV B, CDV A: the shift uses the combo value, the source is always A
+        assert_eq!(run_program(&[6, 5, 7, 3], 64, 2, 0).0, (64, 16, 8));
+        // JNZ not taken when A is 0
+        assert_eq!(run_program(&[3, 4, 5, 1], 0, 0, 0).1, vec![1]);
+    }
+
+    #[test]
+    fn vm_trace() {
+        let mut machine =
+            parse("Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4,3,0\n");
+        let mut trace = Vec::<(usize, Instruction)>::new();
+        machine.run_with_trace(&mut |m, ins| trace.push((m.register_a, *ins)));
+
+        // 10 loops of ADV 1, OUT A, JNZ 0
+        assert_eq!(trace.len(), 30);
+        assert_eq!(
+            trace[..4],
+            [
+                (729, Adv(ComboOperand(1))),
+                (364, Out(ComboOperand(4))),
+                (364, Jnz(LiteralOperand(0))),
+                (364, Adv(ComboOperand(1))),
+            ]
+        );
+        assert_eq!(trace[29], (0, Jnz(LiteralOperand(0))));
+        assert_eq!(machine.output_string(), "4,6,3,5,6,3,5,2,1,0");
+    }
+
+    #[test]
+    fn vm_quine_check() {
+        let mut machine = Machine::with_program(&[0, 3, 5, 4, 3, 0]);
+        machine.reset_with_register(117440);
+        assert!(machine.run_until_halt_or_non_quine());
+        machine.reset_with_register(117441);
+        assert!(machine.run_until_halt_or_non_quine());
+        machine.reset_with_register(2024);
+        assert!(!machine.run_until_halt_or_non_quine());
+        // outputs 0,1: only the first value matches
+        machine.reset_with_register(64);
+        assert!(machine.run_until_halt_or_non_quine_or_outlen(1));
+        machine.reset_with_register(64);
+        assert!(!machine.run_until_halt_or_non_quine_or_outlen(2));
+    }
+
+    #[test]
+    fn vm_outputs_prefix_of() {
+        // outputs 4,6,3,5,6,3,5,2,1,0
+        let sample = "Register A: 729\nRegister B: 0\nRegister C: 0\n\nProgram: 0,1,5,4,3,0\n";
+        let mut machine = parse(sample);
+        assert!(machine.outputs_prefix_of(&[4, 6, 3, 5, 6, 3, 5, 2, 1, 0]));
+
+        let mut machine = parse(sample);
+        assert!(machine.outputs_prefix_of(&[4, 6, 3, 5, 6, 3, 5, 2, 1, 0, 7]));
+
+        // stops at the first difference
+        let mut machine = parse(sample);
+        assert!(!machine.outputs_prefix_of(&[4, 6, 2, 5, 6, 3, 5, 2, 1, 0]));
+        assert_eq!(machine.output(), &[4, 6, 3]);
+
+        // longer than the target
+        let mut machine = parse(sample);
+        assert!(!machine.outputs_prefix_of(&[4, 6, 3]));
+        assert_eq!(machine.output().len(), 4);
+    }
+
+    #[test]
+    fn vm_disassembly() {
+        let machine = Machine::with_program(&[0, 1, 5, 4, 3, 0]);
+        assert_eq!(
+            machine.assembly_lines(),
+            vec!["00: ADV 1", "02: OUT A % 8", "04: JNZ 0"]
+        );
+        assert_eq!(
+            machine.pseudocode_lines(),
+            vec!["00: A = A >> 1", "02: OUT A % 8", "04: If A != 0 JMP 0"]
+        );
+
+        let machine = Machine::with_program(&[2, 4, 1, 1, 7, 5, 4, 4, 1, 4, 0, 3, 5, 5, 3, 0]);
+        let pseudocode = machine.pseudocode_lines();
+        assert_eq!(pseudocode.len(), 8);
+        assert!(pseudocode.contains(&"12: OUT B % 8".to_string()));
+        assert_eq!(machine.assembly_lines()[2], "04: CDV B");
+    }
+}
@@ -2,71 +2,83 @@
 https://adventofcode.com/2024/day/1
 --- Day 1: Historian Hysteria ---
  */
+use aoc::input::parse_row_ints;
+use aoc::solution::Answer;
 use std::collections::HashMap;
 use std::io;
+use std::io::prelude::*;
 use std::iter::zip;
-use std::str::FromStr;
 
-fn main() {
-    // Construct the two lists of location IDs.
+// Sum of the differences between the two lists paired by rank
+fn total_distance(a: &[i64], b: &[i64]) -> i64 {
     // Computing the difference cannot be done on the fly because lists
     // must be sorted first.
-    let mut list_a = Vec::<i32>::new();
-    let mut list_b = Vec::<i32>::new();
-
-    let mut input = String::new();
-    loop {
-        match io::stdin().read_line(&mut input) {
-            Err(_) => {
-                panic!("input error, exit");
-            }
-            Ok(0) => {
-                eprintln!("Eof detected");
-                break;
-            }
-            Ok(_) => {
-                // remove the \n
-                let input_clean = input.trim();
-                // Yes for some reason the puzzle input uses 3 spaces as separator.
-                let ids: Vec<i32> = input_clean
-                    .split("   ")
-                    .map(|i| i32::from_str(i).unwrap())
-                    .collect();
-                list_a.push(ids[0]);
-                list_b.push(ids[1]);
-            }
-        }
-        // must clear for next loop
-        input = String::from("");
-    }
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort();
+    b.sort();
 
-    list_a.sort();
-    list_b.sort();
+    zip(a, b).map(|(a, b)| (a - b).abs()).sum()
+}
 
+// Sum of each value of "a" multiplied by its number of occurences in "b"
+fn similarity(a: &[i64], b: &[i64]) -> i64 {
     // Count the occurence of each unique "location ids" in each list
-    let mut count_a: HashMap<i32, usize> = HashMap::new();
-    for x in &list_a {
+    let mut count_a: HashMap<i64, i64> = HashMap::new();
+    for x in a {
         *count_a.entry(*x).or_default() += 1;
     }
 
-    let mut count_b: HashMap<i32, usize> = HashMap::new();
-    for x in &list_b {
+    let mut count_b: HashMap<i64, i64> = HashMap::new();
+    for x in b {
         *count_b.entry(*x).or_default() += 1;
     }
 
-    let diffs = zip(list_a, list_b).map(|(a, b)| (a - b).abs());
-    let total_distance: i32 = diffs.sum();
+    count_a
+        .into_iter()
+        .map(|(k, v)| k * v * count_b.get(&k).copied().unwrap_or_default())
+        .sum()
+}
 
-    println!("total difference = {total_distance}");
+fn solve(input: &str) -> Answer {
+    // Construct the two lists of location IDs.
+    let mut list_a = Vec::<i64>::new();
+    let mut list_b = Vec::<i64>::new();
 
-    // Could be done with a 1-liner fold() but too unreadable with
-    // all the necessary type conversion
-    let mut score: i64 = 0;
-    for (k, v) in count_a.into_iter() {
-        let m1: i64 = (k as i64) * (v as i64);
-        let m2: i64 = *count_b.entry(k).or_default() as i64;
-        score += m1 * m2;
+    for line in input.lines() {
+        // The puzzle input uses 3 spaces as separator, handled by the
+        // whitespace split of parse_row_ints().
+        let ids: Vec<i64> = parse_row_ints(line);
+        list_a.push(ids[0]);
+        list_b.push(ids[1]);
     }
 
-    println!("Similarity score = {score}");
+    // Part 1 is the total difference, Part 2 the similarity score
+    Answer::new(
+        total_distance(&list_a, &list_b),
+        similarity(&list_a, &list_b),
+    )
+}
+
+fn main() {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .expect("input error, exit");
+
+    print!("{}", solve(&input));
+}
+
+#[test]
+fn check_sample() {
+    let sample = "3   4\n4   3\n2   5\n1   3\n3   9\n3   3\n";
+    assert_eq!(solve(sample), Answer::new(11, 31));
+}
+
+#[test]
+fn check_total_distance_similarity() {
+    let a = [3, 4, 2, 1, 3, 3];
+    let b = [4, 3, 5, 3, 9, 3];
+    assert_eq!(total_distance(&a, &b), 11);
+    assert_eq!(similarity(&a, &b), 31);
 }
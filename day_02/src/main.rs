@@ -3,39 +3,53 @@ https://adventofcode.com/2024/day/2
 --- Day 2: Red-Nosed Reports ---
  */
 
+use aoc::input::parse_row_ints;
+use aoc::parse::all_increasing_within;
 use std::io;
-use std::str::FromStr;
-
-fn is_report_safe(report: &[i32]) -> bool {
-    let delta: Vec<i32> = report.windows(2).map(|w| w[1] - w[0]).collect();
-    //eprintln!("delta of {:?} = {:?}", report, delta);
 
+fn is_report_safe(report: &[i64]) -> bool {
     // Safe == delta are all in [-3,-1] or all in [1,3].
-    // Test and perform early exit in a for loop.
-    // Map or filter would require scanning all and
-    // then counting the number of bad difference which is more
-    // annoying.
-    let mut direction: i32 = 0;
-    for d in &delta {
-        let d = *d;
-        if !(1..=3).contains(&d.abs()) {
-            // bad range anyway
-            return false;
-        }
-        if direction == 0 {
-            // Memorize initial levels direction.
-            direction = d;
-        } else if d * direction < 0 {
-            // mixed increase and decrease
-            return false;
+    all_increasing_within(report, 1, 3) || all_increasing_within(report, -3, -1)
+}
+
+// Same as is_report_safe() on a stream of levels, checking both
+// directions in a single pass.
+fn are_levels_safe(levels: impl Iterator<Item = i64>) -> bool {
+    let (mut increasing, mut decreasing) = (true, true);
+    let mut previous: Option<i64> = None;
+    for level in levels {
+        if let Some(p) = previous {
+            increasing &= (1..=3).contains(&(level - p));
+            decreasing &= (-3..=-1).contains(&(level - p));
+            if !increasing && !decreasing {
+                return false;
+            }
         }
+        previous = Some(level);
     }
-
-    // All checks passed.
     true
 }
 
-fn is_report_safe_with_dampener(report: &Vec<i32>) -> bool {
+// Same as is_report_safe_with_dampener(), but instead of cloning the
+// report for each removal, check it through an iterator omitting
+// the level at position k.
+fn is_report_safe_dampened(report: &[i64]) -> bool {
+    if is_report_safe(report) {
+        return true;
+    }
+    (0..report.len()).any(|k| {
+        are_levels_safe(
+            report
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| *i != k)
+                .map(|(_, level)| *level),
+        )
+    })
+}
+
+#[cfg(test)]
+fn is_report_safe_with_dampener(report: &[i64]) -> bool {
     // Dampening works by removing a single level,
     // not a single delta between levels.
     // No simple formula a priori to detect "the" wrong
@@ -48,7 +62,7 @@ fn is_report_safe_with_dampener(report: &Vec<i32>) -> bool {
     } else {
         // retry by checking all possible 1-element removal.
         for k in 0..report.len() {
-            let mut test_report = report.clone();
+            let mut test_report = report.to_vec();
             test_report.remove(k);
             if is_report_safe(&test_report) {
                 //eprintln!("Made {:?} safe by removing element {k}", report);
@@ -61,7 +75,7 @@ fn is_report_safe_with_dampener(report: &Vec<i32>) -> bool {
 
 fn main() {
     // List of reports, which are lists of levels
-    let mut reports = Vec::<Vec<i32>>::new();
+    let mut reports = Vec::<Vec<i64>>::new();
 
     let mut input = String::new();
     loop {
@@ -70,16 +84,13 @@ fn main() {
                 panic!("input error, exit");
             }
             Ok(0) => {
-                eprintln!("Eof detected");
+                if aoc::args::is_debug() {
+                    eprintln!("Eof detected");
+                }
                 break;
             }
             Ok(_) => {
-                // remove the \n
-                let input_clean = input.trim();
-                let levels: Vec<i32> = input_clean
-                    .split(' ')
-                    .map(|i| i32::from_str(i).unwrap())
-                    .collect();
+                let levels: Vec<i64> = parse_row_ints(&input);
                 reports.push(levels);
             }
         }
@@ -94,7 +105,44 @@ fn main() {
     // Part 2
     let safe_count: u32 = reports
         .iter()
-        .map(|r| is_report_safe_with_dampener(r) as u32)
+        .map(|r| is_report_safe_dampened(r) as u32)
         .sum();
     println!("Safe reports with dampener = {safe_count}");
 }
+
+#[test]
+fn check_dampened_reports() {
+    let reports: Vec<Vec<i64>> = include_str!("../sample.txt")
+        .lines()
+        .map(parse_row_ints)
+        .collect();
+    let safe_count = reports.iter().filter(|r| is_report_safe(r)).count();
+    assert_eq!(safe_count, 2);
+    let safe_count = reports
+        .iter()
+        .filter(|r| is_report_safe_dampened(r))
+        .count();
+    assert_eq!(safe_count, 4);
+
+    // Pseudo-random (LCG) reports, with small steps so that
+    // some of them are safe.
+    let mut rng = aoc::testutil::Lcg::new(12345);
+    let mut next = || rng.next().unwrap() as i64;
+    for _ in 0..2000 {
+        let len = 1 + next() % 8;
+        let direction = if next() % 2 == 0 { 1 } else { -1 };
+        let mut level = next() % 100;
+        let report: Vec<i64> = (0..len)
+            .map(|_| {
+                level += direction * (next() % 5) - (next() % 7 == 0) as i64 * 4;
+                level
+            })
+            .collect();
+        assert_eq!(
+            is_report_safe_dampened(&report),
+            is_report_safe_with_dampener(&report),
+            "{:?}",
+            report
+        );
+    }
+}
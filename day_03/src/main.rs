@@ -5,9 +5,10 @@ https://adventofcode.com/2024/day/3
 
 use regex::Regex;
 use std::io;
+use std::io::prelude::*;
 use std::str::FromStr;
 
-fn scan_muls(inputs: &Vec<String>) -> u64 {
+fn sum_muls(input: &str) -> u64 {
     // Strictly speaking the regex crate matches any
     // unicode digits on \d, not just ascii 0-9, so be explicit.
     // We use Capture groups to return the two numbers arguments
@@ -15,19 +16,19 @@ fn scan_muls(inputs: &Vec<String>) -> u64 {
     let re = Regex::new(r"mul\(([0-9]+),([0-9]+)\)").unwrap();
 
     let mut result: u64 = 0;
-    for s in inputs {
-        for (_, [arg1, arg2]) in re.captures_iter(s).map(|c| c.extract()) {
-            let arg1 = u64::from_str(arg1).unwrap();
-            let arg2 = u64::from_str(arg2).unwrap();
-            // eprintln!("parsed mul {arg1} * {arg2}");
-            result += arg1 * arg2;
-        }
+    for (_, [arg1, arg2]) in re.captures_iter(input).map(|c| c.extract()) {
+        let arg1 = u64::from_str(arg1).unwrap();
+        let arg2 = u64::from_str(arg2).unwrap();
+        // eprintln!("parsed mul {arg1} * {arg2}");
+        result += arg1 * arg2;
     }
 
     result
 }
 
-fn scan_muls_do_dont(inputs: &Vec<String>) -> u64 {
+// The input is scanned as a single stream of characters:
+// the do/don't state must carry over line breaks.
+fn sum_muls_conditional(input: &str) -> u64 {
     // Add a capture group matching the conditional command.
     // Use named capture groups to distinguish the different cases.
     // We must use a single regex and not multiple, to be able to iterate
@@ -38,27 +39,25 @@ fn scan_muls_do_dont(inputs: &Vec<String>) -> u64 {
     let mut enabled: bool = true;
     let mut result: u64 = 0;
 
-    for s in inputs {
-        for cap in re.captures_iter(s) {
-            if let Some(_) = cap.name("do") {
-                enabled = true;
-            } else if let Some(_) = cap.name("dont") {
-                enabled = false;
-            } else if enabled {
-                if let Some(arg1) = cap.name("arg1") {
-                    if let Some(arg2) = cap.name("arg2") {
-                        let arg1 = arg1.as_str();
-                        let arg2 = arg2.as_str();
-                        //eprintln!("parsed enabled mul  {arg1} * {arg2}");
-                        let arg1 = u64::from_str(arg1).unwrap();
-                        let arg2 = u64::from_str(arg2).unwrap();
-                        result += arg1 * arg2;
-                    } else {
-                        panic!("regex matched neither do, dont or arg2");
-                    }
+    for cap in re.captures_iter(input) {
+        if let Some(_) = cap.name("do") {
+            enabled = true;
+        } else if let Some(_) = cap.name("dont") {
+            enabled = false;
+        } else if enabled {
+            if let Some(arg1) = cap.name("arg1") {
+                if let Some(arg2) = cap.name("arg2") {
+                    let arg1 = arg1.as_str();
+                    let arg2 = arg2.as_str();
+                    //eprintln!("parsed enabled mul  {arg1} * {arg2}");
+                    let arg1 = u64::from_str(arg1).unwrap();
+                    let arg2 = u64::from_str(arg2).unwrap();
+                    result += arg1 * arg2;
                 } else {
-                    panic!("regex matched neither do, dont or arg1");
+                    panic!("regex matched neither do, dont or arg2");
                 }
+            } else {
+                panic!("regex matched neither do, dont or arg1");
             }
         }
     }
@@ -67,33 +66,38 @@ fn scan_muls_do_dont(inputs: &Vec<String>) -> u64 {
 }
 
 fn main() {
-    let mut lines = Vec::<String>::new();
-
     let mut input = String::new();
-    loop {
-        match io::stdin().read_line(&mut input) {
-            Err(_) => {
-                panic!("input error, exit");
-            }
-            Ok(0) => {
-                eprintln!("Eof detected");
-                break;
-            }
-            Ok(_) => {
-                // remove the \n
-                let input_clean = input.trim();
-                lines.push(input_clean.to_string());
-            }
-        }
-        // must clear for next loop
-        input = String::from("");
-    }
+    io::stdin()
+        .read_to_string(&mut input)
+        .expect("input error, exit");
 
     // Part 1
-    let mulsum: u64 = scan_muls(&lines);
+    let mulsum: u64 = sum_muls(&input);
     println!("mul = {mulsum}");
 
     // Part 2
-    let mulsum: u64 = scan_muls_do_dont(&lines);
+    let mulsum: u64 = sum_muls_conditional(&input);
     println!("mul do/don't= {mulsum}");
 }
+
+#[test]
+fn check_sample() {
+    let sample = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
+    assert_eq!(sum_muls(sample), 161);
+    let sample = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+    assert_eq!(sum_muls_conditional(sample), 48);
+}
+
+#[test]
+fn check_do_dont_across_lines() {
+    // don't() region spanning over two line breaks
+    let input = "mul(2,3)don't()mul(4,5)\nmul(6,7)\nmul(1,1)do()mul(10,10)\nmul(3,3)";
+    assert_eq!(sum_muls(input), 6 + 20 + 42 + 1 + 100 + 9);
+    assert_eq!(sum_muls_conditional(input), 6 + 100 + 9);
+}
+
+#[test]
+fn check_sample_files() {
+    assert_eq!(sum_muls(include_str!("../sample.txt")), 161);
+    assert_eq!(sum_muls_conditional(include_str!("../sample_2.txt")), 48);
+}
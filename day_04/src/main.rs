@@ -21,7 +21,7 @@ fn count_xmas(grid: &Grid) -> usize {
     // diagonal down-right, on all coordinates.
 
     // Used to display the result at the end
-    let debug = false;
+    let debug = aoc::args::is_debug();
     let mut debug_grid = grid.clone();
 
     let mut found: usize = 0;
@@ -91,7 +91,7 @@ fn count_cross_mas(grid: &Grid) -> usize {
     let searched = vec![vec!['M', 'A', 'S'], vec!['S', 'A', 'M']];
 
     // Used to display the result at the end
-    let debug = false;
+    let debug = aoc::args::is_debug();
     let mut debug_grid = grid.clone();
 
     let mut found: usize = 0;
@@ -149,7 +149,9 @@ fn main() {
                 panic!("input error, exit");
             }
             Ok(0) => {
-                eprintln!("Eof detected");
+                if aoc::args::is_debug() {
+                    eprintln!("Eof detected");
+                }
                 break;
             }
             Ok(_) => {
@@ -126,7 +126,9 @@ fn main() {
                 panic!("input error, exit");
             }
             Ok(0) => {
-                eprintln!("Eof detected");
+                if aoc::args::is_debug() {
+                    eprintln!("Eof detected");
+                }
                 break;
             }
             Ok(_) => {
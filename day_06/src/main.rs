@@ -3,6 +3,8 @@ https://adventofcode.com/2024/day/6
 --- Day 6: Guard Gallivant ---
  */
 
+use aoc::direction::Direction;
+use aoc::grid::DirectionVisited;
 use std::boxed::Box;
 use std::io;
 
@@ -96,38 +98,12 @@ impl Grid<bool> {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
-enum Direction {
-    Left,
-    Right,
-    Up,
-    Down,
-}
 use Direction::*;
 
-impl Direction {
-    fn rotate_right(&self) -> Direction {
-        match self {
-            Left => Up,
-            Up => Right,
-            Right => Down,
-            Down => Left,
-        }
-    }
-}
-
 impl<T> Grid<T> {
     // Return Some(newx,newy) after moving by direction, else None if out-of-bounds
-    #[rustfmt::skip]
     fn get_next_coordinates(&self, p: (usize, usize), d: Direction) -> Option<(usize, usize)> {
-        let x = p.0;
-        let y = p.1;
-        match d {
-            Left =>  if x == 0             { None } else { Some((x-1, y)) },
-            Right => if x+1 >= self.width  { None } else { Some((x+1, y)) },
-            Up =>    if y == 0             { None } else { Some((x, y-1)) },
-            Down =>  if y+1 >= self.height { None } else { Some((x, y+1)) },
-        }
+        d.step_bounded(p, self.width, self.height)
     }
 }
 
@@ -161,22 +137,14 @@ fn count_positions(map: &Grid<bool>, start: (usize, usize)) -> usize {
         }
     }
 
-    // if debug
-    eprintln!("Travel path:");
-    travel_map.pretty_print_bool();
+    if aoc::args::is_verbose() {
+        eprintln!("Travel path:");
+        travel_map.pretty_print_bool();
+    }
 
     count_visit
 }
 
-fn u8FromDirection(d: Direction) -> u8 {
-    match d {
-        Up => 0b0001,
-        Right => 0b0010,
-        Down => 0b0100,
-        Left => 0b1000,
-    }
-}
-
 // Return true if the path from a starting position leads to an
 // infinite loop
 fn check_has_loop(map: &Grid<bool>, start: (usize, usize)) -> bool {
@@ -187,8 +155,8 @@ fn check_has_loop(map: &Grid<bool>, start: (usize, usize)) -> bool {
     // pass in a location. An infinite loop is detected as soon
     // as the same direction is used again. Simply crossing it
     // by a different direction is not enough.
-    let mut travel_map = Grid::<u8>::new(map.width, map.height, 0);
-    travel_map.set(start.0, start.1, u8FromDirection(direction));
+    let mut travel_map = DirectionVisited::new(map.width, map.height);
+    travel_map.mark(start.0, start.1, direction);
 
     loop {
         if let Some(new_coord) = map.get_next_coordinates(pos, direction) {
@@ -198,12 +166,9 @@ fn check_has_loop(map: &Grid<bool>, start: (usize, usize)) -> bool {
             } else {
                 pos = new_coord;
                 // already visited this space in the same direction ?
-                let oldDir = *travel_map.get(pos.0, pos.1);
-                // bitmap test
-                if oldDir & u8FromDirection(direction) != 0 {
+                if travel_map.mark(pos.0, pos.1, direction) {
                     return true;
                 }
-                travel_map.set(pos.0, pos.1, oldDir | u8FromDirection(direction));
             }
         } else {
             // went out of the map
@@ -265,9 +230,10 @@ fn count_obstructions(map: &Grid<bool>, start: (usize, usize)) -> usize {
         }
     }
 
-    // if debug
-    eprintln!("Map of new inf-loop obstructions:");
-    valid_obstruction_map.pretty_print_bool();
+    if aoc::args::is_verbose() {
+        eprintln!("Map of new inf-loop obstructions:");
+        valid_obstruction_map.pretty_print_bool();
+    }
 
     valid_obstructions
 }
@@ -309,8 +275,7 @@ fn main() {
     }
 
     let map = Grid::<bool>::from_vec(map);
-    let debug = true;
-    if debug {
+    if aoc::args::is_verbose() {
         map.pretty_print_bool();
         eprintln!("Starting position is at {:?}", start);
     }
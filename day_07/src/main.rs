@@ -6,8 +6,6 @@ https://adventofcode.com/2024/day/7
 use std::io;
 use std::str::FromStr;
 
-const DEBUG: bool = true;
-
 #[derive(Debug)]
 struct Equation {
     value: usize,
@@ -46,13 +44,13 @@ fn can_solve(eq: &Equation) -> bool {
 
     for k in 0..=operators_map {
         if compute_equation_with_map(&eq.operands, k) == eq.value {
-            if DEBUG {
+            if aoc::args::is_debug() {
                 eprintln!("Solved {:?} with operators map {:b}", eq, k);
             }
             return true;
         }
     }
-    if DEBUG {
+    if aoc::args::is_debug() {
         eprintln!("Cannot solve {:?}", eq);
     }
     // No found combination
@@ -118,14 +116,14 @@ fn can_solve_with_concat(eq: &Equation) -> bool {
     let operators_concat: u32 = (1 << 2 * (eq.operands.len())) - 1;
     for k in 0..=operators_concat {
         if compute_equation_with_concat(&eq.operands, k) == eq.value {
-            if DEBUG {
+            if aoc::args::is_debug() {
                 eprintln!("Solved by concat only {:?} with operators map {:b}", eq, k);
             }
             return true;
         }
     }
 
-    if DEBUG {
+    if aoc::args::is_debug() {
         eprintln!("Cannot solve at all {:?}", eq);
     }
     // No found combination
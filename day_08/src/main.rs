@@ -3,6 +3,9 @@ https://adventofcode.com/2024/day/8
 --- Day 8: Resonant Collinearity ---
  */
 
+use aoc::args;
+use aoc::colors;
+use aoc::grid::Grid;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::io;
@@ -121,6 +124,56 @@ fn count_harmonics_locations(input: &HashMap<char, Vec<Coord>>, bound: Coord) ->
     locations.len()
 }
 
+// Return a map of the antinodes of each frequency, for part 1 or part 2 (harmonics).
+// Each antinode cell holds the frequency that created it, or '*' if
+// shared by multiple frequencies.
+fn antinodes_grid(input: &HashMap<char, Vec<Coord>>, bound: Coord, harmonics: bool) -> Grid<char> {
+    let mut grid = Grid::<char>::new(bound.0 as usize, bound.1 as usize, '.');
+
+    for (freq, antennas) in input {
+        let mut locations = HashSet::<Coord>::new();
+        if harmonics {
+            set_freq_harmonics_locations(antennas, &mut locations, bound);
+        } else {
+            set_freq_antinodes_locations(antennas, &mut locations, bound);
+        }
+        for c in locations {
+            let (x, y) = (c.0 as usize, c.1 as usize);
+            let mark = if grid.get(x, y) == '.' { *freq } else { '*' };
+            grid.set(x, y, mark);
+        }
+    }
+
+    grid
+}
+
+fn freq_to_color(freq: char) -> &'static str {
+    // Skip black and white
+    colors::BG_COLORS[1 + (freq as usize) % 6]
+}
+
+// Print the antennas, with the antinodes colored by frequency
+fn debug_print_antinodes(input: &HashMap<char, Vec<Coord>>, antinodes: &Grid<char>) {
+    let mut antennas = Grid::<char>::new(antinodes.width, antinodes.height, '.');
+    for (freq, list) in input {
+        for c in list {
+            antennas.set(c.0 as usize, c.1 as usize, *freq);
+        }
+    }
+
+    antennas.pretty_print_lambda_with_overlay(antinodes, &|a, n, _xy| match (a, n) {
+        ('.', '.') => ".".to_string(),
+        ('.', '*') => format!(
+            "{}#{}",
+            colors::BG_BRIGHT_COLORS[colors::WHITE],
+            colors::ANSI_RESET
+        ),
+        ('.', n) => format!("{}#{}", freq_to_color(n), colors::ANSI_RESET),
+        (a, '.') => a.to_string(),
+        (a, n) => format!("{}{a}{}", freq_to_color(n), colors::ANSI_RESET),
+    });
+}
+
 fn main() {
     // each Frequency (1-letter input) maps to a list
     // of coordinates of each antenna.
@@ -171,4 +224,81 @@ fn main() {
         "Part 2 = {}",
         count_harmonics_locations(&antenna_map, bound)
     );
+
+    if args::is_debug() {
+        debug_print_antinodes(&antenna_map, &antinodes_grid(&antenna_map, bound, false));
+        debug_print_antinodes(&antenna_map, &antinodes_grid(&antenna_map, bound, true));
+    }
+}
+
+#[test]
+fn check_sample_antinodes_grid() {
+    let sample = [
+        "............",
+        "........0...",
+        ".....0......",
+        ".......0....",
+        "....0.......",
+        "......A.....",
+        "............",
+        "............",
+        "........A...",
+        ".........A..",
+        "............",
+        "............",
+    ];
+    let mut antenna_map = HashMap::<char, Vec<Coord>>::new();
+    for (y, row) in sample.iter().enumerate() {
+        for (x, freq) in row.char_indices().filter(|(_, f)| *f != '.') {
+            antenna_map
+                .entry(freq)
+                .or_default()
+                .push(Coord(x as isize, y as isize));
+        }
+    }
+    let bound = Coord(12, 12);
+
+    // From the puzzle description, with the antinode hidden
+    // under the top 'A' antenna also shown.
+    let expected = [
+        "......#....#",
+        "...#....0...",
+        "....#0....#.",
+        "..#....0....",
+        "....0....#..",
+        ".#....#.....",
+        "...#........",
+        "#......#....",
+        "........A...",
+        ".........A..",
+        "..........#.",
+        "..........#.",
+    ];
+    let antinodes = antinodes_grid(&antenna_map, bound, false);
+    let mut rendered = antinodes.clone();
+    for (y, row) in sample.iter().enumerate() {
+        for (x, antenna) in row.chars().enumerate() {
+            let c = if antinodes.get(x, y) != '.' {
+                '#'
+            } else {
+                antenna
+            };
+            rendered.set(x, y, c);
+        }
+    }
+    aoc::testutil::assert_grid_eq(&rendered, &expected.join("\n"));
+
+    let count = |g: &Grid<char>| {
+        (0..g.height)
+            .map(|y| g.get_row_slice(y).iter().filter(|c| **c != '.').count())
+            .sum::<usize>()
+    };
+    assert_eq!(
+        count(&antinodes),
+        count_antinode_locations(&antenna_map, bound)
+    );
+    assert_eq!(
+        count(&antinodes_grid(&antenna_map, bound, true)),
+        count_harmonics_locations(&antenna_map, bound)
+    );
 }
@@ -3,10 +3,12 @@ https://adventofcode.com/2024/day/9
 --- Day 9: Disk Fragmenter ---
  */
 
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::io;
 use std::io::prelude::*;
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum DiskMap {
     // A file, with its ID
     File(usize),
@@ -16,11 +18,16 @@ enum DiskMap {
 
 use DiskMap::*;
 
+// The block-by-block implementations below were the initial solutions.
+// They are kept (for tests only) as a reference for the compact
+// span-based ones.
+
 // compared to DiskMap, each block also maintain
 // the number of other blocks on its left and on its right
 // of the same contigous file/empty. (so 0,0 for a span
 // of size 1)
 
+#[cfg(test)]
 #[derive(Clone, Copy, Debug)]
 enum DiskMapLen {
     // A file, with its ID, and its left/right remaining size
@@ -31,6 +38,7 @@ enum DiskMapLen {
 
 // Convert the run-length encoding of file/empty space, into an explicit
 // list of multiple blocks and indexed files.
+#[cfg(test)]
 fn rle_to_blocks(input: &Vec<usize>) -> Vec<DiskMap> {
     let mut blocks = Vec::<DiskMap>::new();
 
@@ -48,6 +56,7 @@ fn rle_to_blocks(input: &Vec<usize>) -> Vec<DiskMap> {
     blocks
 }
 
+#[cfg(test)]
 fn defrag(input: &Vec<usize>) -> Vec<DiskMap> {
     let mut blocks = rle_to_blocks(input);
     // let reverse = blocks.clone().reverse(); // actually not useful
@@ -79,6 +88,7 @@ fn defrag(input: &Vec<usize>) -> Vec<DiskMap> {
 
 // Convert the run-length encoding of file/empty space, into an explicit
 // list of multiple blocks and indexed files.
+#[cfg(test)]
 fn rle_to_blocks_length(input: &Vec<usize>) -> Vec<DiskMapLen> {
     let mut blocks = Vec::<DiskMapLen>::new();
 
@@ -104,6 +114,7 @@ fn rle_to_blocks_length(input: &Vec<usize>) -> Vec<DiskMapLen> {
 }
 
 // This is horrible.
+#[cfg(test)]
 fn defrag_contiguous(input: &Vec<usize>) -> Vec<DiskMap> {
     let mut blocks = rle_to_blocks_length(input);
 
@@ -118,7 +129,9 @@ fn defrag_contiguous(input: &Vec<usize>) -> Vec<DiskMap> {
             DiskMapLen::File(id, llen, _) => {
                 // File id 0 is at the start of disk, no need to move, end condition.
                 if id == 0 {
-                    eprintln!("File 0, exit");
+                    if aoc::args::is_debug() {
+                        eprintln!("File 0, exit");
+                    }
                     break;
                 }
 
@@ -180,6 +193,7 @@ fn defrag_contiguous(input: &Vec<usize>) -> Vec<DiskMap> {
         .collect()
 }
 
+#[cfg(test)]
 fn checksum(defragged: &Vec<DiskMap>) -> usize {
     //eprintln!("Defrag =  {:?} ", defragged);
     let mut checksum: usize = 0;
@@ -198,16 +212,202 @@ fn checksum(defragged: &Vec<DiskMap>) -> usize {
     checksum
 }
 
+#[cfg(test)]
 fn defrag_checksum(input: &Vec<usize>) -> usize {
     let defragged = defrag(input);
     checksum(&defragged)
 }
 
+#[cfg(test)]
 fn defrag_contiguous_checksum(input: &Vec<usize>) -> usize {
     let defragged = defrag_contiguous(input);
     checksum(&defragged)
 }
 
+// Compact representation of the disk: a span of "len" contiguous blocks
+// of the same file or of empty space.
+// This is the same as the input run-length encoding, but the spans
+// can be split and moved around without expanding every single block.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Span {
+    kind: DiskMap,
+    len: usize,
+}
+
+// Convert the run-length encoding of file/empty space into indexed spans.
+// Spans of size 0 are dropped.
+fn rle_to_spans(input: &[usize]) -> Vec<Span> {
+    let mut spans = Vec::<Span>::with_capacity(input.len());
+
+    for (k, length) in input.iter().enumerate() {
+        if *length == 0 {
+            continue;
+        }
+        let kind = if k % 2 == 0 { File(k / 2) } else { Empty };
+        spans.push(Span { kind, len: *length });
+    }
+
+    spans
+}
+
+// Part 1 over spans: fill each empty span from the left with the blocks of
+// the rightmost file span, moving the whole span or only the part that fits.
+// Only the files are returned, the free space all ends up after them.
+fn defrag_spans(input: &[usize]) -> Vec<Span> {
+    let mut spans = rle_to_spans(input);
+    let mut defragged = Vec::<Span>::with_capacity(spans.len());
+
+    let mut scan_free: usize = 0;
+    let mut scan_move: usize = spans.len() - 1;
+
+    while scan_free <= scan_move {
+        match spans[scan_free] {
+            Span { kind: File(_), .. } => {
+                defragged.push(spans[scan_free]);
+                scan_free += 1;
+            }
+            Span {
+                kind: Empty,
+                len: free,
+            } => {
+                let Span {
+                    kind,
+                    len: file_len,
+                } = spans[scan_move];
+                if kind == Empty {
+                    scan_move -= 1;
+                    continue;
+                }
+                let moved = free.min(file_len);
+                defragged.push(Span { kind, len: moved });
+                spans[scan_free].len -= moved;
+                spans[scan_move].len -= moved;
+                if spans[scan_free].len == 0 {
+                    scan_free += 1;
+                }
+                if spans[scan_move].len == 0 {
+                    scan_move -= 1;
+                }
+            }
+        }
+    }
+
+    defragged
+}
+
+// Part 2 over spans: move each whole file, from the last ID, into the
+// leftmost empty span large enough on its left.
+// The space freed by a moved file is never reused: only files
+// with a lower ID, which are already on its left, would try to move after.
+#[cfg(test)]
+fn defrag_contiguous_spans(input: &[usize]) -> Vec<Span> {
+    let mut spans = rle_to_spans(input);
+    let last_id = (input.len() - 1) / 2;
+
+    for id in (1..=last_id).rev() {
+        let Some(file_pos) = spans.iter().rposition(|s| s.kind == File(id)) else {
+            // Empty file
+            continue;
+        };
+        let file_len = spans[file_pos].len;
+        let Some(free_pos) = spans[..file_pos]
+            .iter()
+            .position(|s| s.kind == Empty && s.len >= file_len)
+        else {
+            continue;
+        };
+
+        let remaining = spans[free_pos].len - file_len;
+        spans[file_pos].kind = Empty;
+        spans[free_pos] = Span {
+            kind: File(id),
+            len: file_len,
+        };
+        if remaining > 0 {
+            spans.insert(
+                free_pos + 1,
+                Span {
+                    kind: Empty,
+                    len: remaining,
+                },
+            );
+        }
+    }
+
+    spans
+}
+
+// Checksum of the "len" blocks of file "id" starting at block "pos":
+// the positions pos..pos+len sum to len*pos + len*(len-1)/2
+fn file_checksum(id: usize, pos: usize, len: usize) -> usize {
+    id * (len * pos + len * len.saturating_sub(1) / 2)
+}
+
+// Same as checksum() but directly from the spans.
+fn spans_checksum(spans: &[Span]) -> usize {
+    let mut checksum: usize = 0;
+    let mut pos: usize = 0;
+
+    for span in spans {
+        if let File(id) = span.kind {
+            checksum += file_checksum(id, pos, span.len);
+        }
+        pos += span.len;
+    }
+    checksum
+}
+
+// Part 2 again, without scanning the free spans from the start for
+// each file: the start positions of the free spans are kept in one
+// min-heap per span size (sizes are a single digit), so the leftmost
+// span of at least a given size is the smallest of the heaps tops.
+// Returns the checksum of the files at their final position.
+fn defrag_contiguous_heaps_checksum(input: &[usize]) -> usize {
+    let mut free: [BinaryHeap<Reverse<usize>>; 10] = Default::default();
+    // (id, start position, len) of each file
+    let mut files = Vec::<(usize, usize, usize)>::with_capacity(input.len() / 2 + 1);
+
+    let mut pos = 0;
+    for (k, length) in input.iter().enumerate() {
+        if k % 2 == 0 {
+            files.push((k / 2, pos, *length));
+        } else if *length > 0 {
+            free[*length].push(Reverse(pos));
+        }
+        pos += length;
+    }
+
+    let mut checksum: usize = 0;
+    for (id, file_pos, file_len) in files.into_iter().rev() {
+        let best = (file_len..free.len())
+            .filter_map(|size| free[size].peek().map(|Reverse(p)| (*p, size)))
+            .min()
+            .filter(|(p, _)| *p < file_pos);
+
+        let Some((free_pos, size)) = best else {
+            checksum += file_checksum(id, file_pos, file_len);
+            continue;
+        };
+
+        free[size].pop();
+        if size > file_len {
+            free[size - file_len].push(Reverse(free_pos + file_len));
+        }
+        checksum += file_checksum(id, free_pos, file_len);
+    }
+
+    checksum
+}
+
+fn defrag_spans_checksum(input: &[usize]) -> usize {
+    spans_checksum(&defrag_spans(input))
+}
+
+#[cfg(test)]
+fn defrag_contiguous_spans_checksum(input: &[usize]) -> usize {
+    spans_checksum(&defrag_contiguous_spans(input))
+}
+
 fn main() {
     let stdin = io::stdin();
     // There is only one big line in the input.
@@ -223,7 +423,47 @@ fn main() {
         .map(|c| c.to_digit(10).unwrap() as usize)
         .collect();
 
-    println!("Part 1 = {}", defrag_checksum(&parsed));
+    println!("Part 1 = {}", defrag_spans_checksum(&parsed));
+
+    println!("Part 2 = {}", defrag_contiguous_heaps_checksum(&parsed));
+}
+
+#[test]
+fn check_spans_match_blocks() {
+    let sample: Vec<usize> = "2333133121414131402"
+        .chars()
+        .map(|c| c.to_digit(10).unwrap() as usize)
+        .collect();
+
+    assert_eq!(defrag_checksum(&sample), 1928);
+    assert_eq!(defrag_spans_checksum(&sample), 1928);
+    assert_eq!(defrag_contiguous_checksum(&sample), 2858);
+    assert_eq!(defrag_contiguous_spans_checksum(&sample), 2858);
+
+    // with some empty files and empty spaces
+    let other = vec![1, 0, 3, 2, 0, 4, 2, 1, 5, 3, 1, 0, 2];
+    assert_eq!(defrag_spans_checksum(&other), defrag_checksum(&other));
+    assert_eq!(
+        defrag_contiguous_spans_checksum(&other),
+        defrag_contiguous_checksum(&other)
+    );
+}
+
+#[test]
+fn check_heaps_match_spans() {
+    let sample: Vec<usize> = "2333133121414131402"
+        .chars()
+        .map(|c| c.to_digit(10).unwrap() as usize)
+        .collect();
+    assert_eq!(defrag_contiguous_heaps_checksum(&sample), 2858);
 
-    println!("Part 2 = {}", defrag_contiguous_checksum(&parsed));
+    // Bigger pseudo-random disk map (LCG), with some zero sizes
+    let big: Vec<usize> = aoc::testutil::Lcg::new(12345)
+        .take(2001)
+        .map(|r| (r % 10) as usize)
+        .collect();
+    assert_eq!(
+        defrag_contiguous_heaps_checksum(&big),
+        defrag_contiguous_spans_checksum(&big)
+    );
 }
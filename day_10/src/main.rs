@@ -3,12 +3,13 @@ https://adventofcode.com/2024/day/10
 --- Day 10: Hoof It ---
  */
 
+use aoc::grid::Grid;
 use std::io;
 //use std::str::FromStr;
 use std::io::prelude::*;
 
 // Recursive sum of rest of trail or trail forks
-fn trail_score(map: &Vec<Vec<usize>>, x: usize, y: usize) -> usize {
+fn trail_score(map: &Grid<usize>, x: usize, y: usize) -> usize {
     // Naive recursion will count the number of PATHs that
     // a trail will lead to an ending 9, but not the
     // number of singular end cell if they are reached
@@ -22,13 +23,13 @@ fn trail_score(map: &Vec<Vec<usize>>, x: usize, y: usize) -> usize {
 
 // Recursive sum of rest of trail or trail forks
 fn trail_score_internal(
-    map: &Vec<Vec<usize>>,
+    map: &Grid<usize>,
     x: usize,
     y: usize,
     locations: &mut Vec<(usize, usize)>,
 ) -> usize {
     let mut score: usize = 0;
-    let elevation = map[y][x];
+    let elevation = map[(x, y)];
 
     // Found end
     if elevation == 9 {
@@ -36,11 +37,8 @@ fn trail_score_internal(
         return 1;
     }
 
-    for (dx, dy) in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
-        let next_x = (x as isize + dx) as usize;
-        let next_y = (y as isize + dy) as usize;
-
-        if map[next_y][next_x] == elevation + 1 {
+    for (next_x, next_y) in map.neighbors4(x, y) {
+        if map[(next_x, next_y)] == elevation + 1 {
             // found potential path to follow
             if locations.contains(&(next_x, next_y)) {
                 // already taken via a different fork
@@ -54,17 +52,17 @@ fn trail_score_internal(
     score
 }
 
-fn trailhead_total_scores(map: &Vec<Vec<usize>>) -> usize {
+fn trailhead_total_scores(map: &Grid<usize>) -> usize {
     // Iterate on the useful interior, ignore borders
-    let width = map[0].len() - 1;
-    let height = map.len() - 1;
+    let width = map.width - 1;
+    let height = map.height - 1;
 
     let mut scores: usize = 0;
 
     for y in 1..height {
         for x in 1..width {
             // trail Head (starting point)
-            if map[y][x] == 0 {
+            if map[(x, y)] == 0 {
                 let single_score = trail_score(map, x, y);
                 //eprintln!("Found trail start at {x},{y}, of score {single_score}");
                 scores += single_score;
@@ -77,9 +75,9 @@ fn trailhead_total_scores(map: &Vec<Vec<usize>>) -> usize {
 
 // Recursive sum of rest of trail or trail forks.
 // actually the same as the first buggy version as part 1 before accounting for multiple paths... WTF
-fn trail_rating(map: &Vec<Vec<usize>>, x: usize, y: usize) -> usize {
+fn trail_rating(map: &Grid<usize>, x: usize, y: usize) -> usize {
     let mut score: usize = 0;
-    let elevation = map[y][x];
+    let elevation = map[(x, y)];
 
     // Found end
     if elevation == 9 {
@@ -87,27 +85,24 @@ fn trail_rating(map: &Vec<Vec<usize>>, x: usize, y: usize) -> usize {
         return 1;
     }
 
-    for (dx, dy) in [(0, 1), (0, -1), (1, 0), (-1, 0)] {
-        let next_x = (x as isize + dx) as usize;
-        let next_y = (y as isize + dy) as usize;
-
-        if map[next_y][next_x] == elevation + 1 {
+    for (next_x, next_y) in map.neighbors4(x, y) {
+        if map[(next_x, next_y)] == elevation + 1 {
             score += trail_rating(map, next_x, next_y);
         }
     }
     score
 }
 
-fn trailhead_total_ratings(map: &Vec<Vec<usize>>) -> usize {
-    let width = map[0].len() - 1;
-    let height = map.len() - 1;
+fn trailhead_total_ratings(map: &Grid<usize>) -> usize {
+    let width = map.width - 1;
+    let height = map.height - 1;
 
     let mut ratings: usize = 0;
 
     for y in 1..height {
         for x in 1..width {
             // trail Head (starting point)
-            if map[y][x] == 0 {
+            if map[(x, y)] == 0 {
                 ratings += trail_rating(map, x, y);
             }
         }
@@ -143,7 +138,9 @@ fn main() {
     // Add barrier to bottom of map
     parsed.push(parsed[0].clone());
 
-    println!("Part 1 = {}", trailhead_total_scores(&parsed));
+    let map = Grid::from_vec(&parsed);
+
+    println!("Part 1 = {}", trailhead_total_scores(&map));
 
-    println!("Part 2 = {}", trailhead_total_ratings(&parsed));
+    println!("Part 2 = {}", trailhead_total_ratings(&map));
 }
@@ -3,6 +3,8 @@ https://adventofcode.com/2024/day/11
 --- Day 11: Plutonian Pebbles ---
  */
 
+use aoc::args;
+use aoc::input::parse_row_ints;
 use num::Integer;
 use std::collections::HashMap;
 use std::io;
@@ -216,12 +218,15 @@ fn expand_value_at_level(
 //    result.len()
 //}
 
-// Takes 2.0s for input (result is on the order of 259593838000000 )
-fn count_2(input: &Vec<usize>) -> usize {
+// Takes 2.0s for input with 75 blinks (result is on the order of 259593838000000 )
+// The expansions cache is not an aoc::cache::Memo: each entry is a history
+// grown one level at a time in place (and read back by the recursion
+// before it is complete), not a value computed once per key.
+fn count_stones(input: &[usize], steps: usize) -> usize {
     let mut expansions = HashMap::<usize, ValueExpansions>::new();
     let mut size = 0;
     for k in input {
-        size += expand_value_at_level(*k, 75, &mut expansions);
+        size += expand_value_at_level(*k, steps, &mut expansions);
     }
 
     size
@@ -237,12 +242,21 @@ fn main() {
         .unwrap()
         .expect("input should contain one line");
 
-    let parsed: Vec<usize> = line
-        .split(' ')
-        .map(|s| usize::from_str(s).unwrap())
-        .collect();
+    let parsed: Vec<usize> = parse_row_ints(&line);
 
     println!("Part 1 = {}", count_1(&parsed));
 
-    println!("Part 2 = {}", count_2(&parsed));
+    // Number of blinks can be changed with --steps=N
+    let steps = args::arg_value("steps").unwrap_or(75);
+    println!("Part 2 = {}", count_stones(&parsed, steps));
+}
+
+#[test]
+fn check_count_stones() {
+    let sample = vec![125, 17];
+    assert_eq!(count_1(&sample), 55312);
+    assert_eq!(count_stones(&sample, 25), 55312);
+    assert_eq!(count_stones(&sample, 6), 22);
+    assert_eq!(count_stones(&sample, 1), 3);
+    assert_eq!(count_stones(&sample, 0), 2);
 }
@@ -221,14 +221,18 @@ fn main() {
     //regions.pretty_print_lambda(&|v| format!("{:03}.", if v > 610 { v } else { 0 } ));
     //regions.pretty_print_lambda(&|v| format!("{:03}.", v));
 
-    eprintln!("Map has {max} contiguous regions");
+    if args::is_debug() {
+        eprintln!("Map has {max} contiguous regions");
+    }
     let costs = fence_cost(&regions, max);
 
     println!("Part 1 = {}", costs.0);
     println!("Part 2 = {}", costs.1);
     let elapsed_process: Duration = Instant::now() - start_process;  // Calculate elapsed time.
-    eprintln!("Time taken for parsing: {:?}", elapsed_parse);
-    eprintln!("Time taken for processing: {:?}", elapsed_process);
-    eprintln!("Total time: {:?}", elapsed_process + elapsed_parse);
+    if !args::quiet() {
+        eprintln!("Time taken for parsing: {:?}", elapsed_parse);
+        eprintln!("Time taken for processing: {:?}", elapsed_process);
+        eprintln!("Total time: {:?}", elapsed_process + elapsed_parse);
+    }
 
 }
@@ -3,11 +3,11 @@ https://adventofcode.com/2024/day/13
 --- Day 13: Claw Contraption ---
  */
 
+use aoc::args;
+use aoc::input::parse_signed_pairs;
 use num::integer::div_rem;
-use regex::Regex;
 use std::io;
 use std::io::prelude::*;
-use std::str::FromStr;
 
 const A_COST: isize = 3;
 const B_COST: isize = 1;
@@ -60,7 +60,9 @@ fn get_token_cost(claw: &ClawMachine) -> Option<isize> {
 
     if a_n.0 < 0 || b_n.0 < 0 {
         // This should not happen
-        eprintln!("Claw {:?} has negative result", claw);
+        if !args::quiet() {
+            eprintln!("Claw {:?} has negative result", claw);
+        }
         return None;
     }
 
@@ -93,20 +95,12 @@ fn minimal_tokens_for_prizes_mega(machines: &Vec<ClawMachine>) -> isize {
 }
 
 fn main() {
-    // This regex covers both the Button and the Prize subtly different formats
-    let re_xy = Regex::new(r".*: X.([0-9]+), Y.([0-9]+)").unwrap();
-
     let lines = io::stdin().lock().lines();
 
-    // Parse all lines with the re_xy (if matching), without
-    // bothering with grouping by 3 (blank lines will be skipped)
-    let xy_iter = lines.filter_map(|line /*: Option<io::Result<String>>*/| {
-        let line = line.ok()?;
-        let caps = re_xy.captures(&line)?;
-        let x = isize::from_str(caps.get(1).unwrap().as_str()).unwrap();
-        let y = isize::from_str(caps.get(2).unwrap().as_str()).unwrap();
-        Some((x, y))
-    });
+    // The pair parser covers both the Button and the Prize subtly different
+    // formats. Parse all lines without bothering with grouping by 3
+    // (blank lines have no pair and will be skipped)
+    let xy_iter = lines.flat_map(|line| parse_signed_pairs(&line.unwrap()));
 
     // There is no .chunks() on iterators, only slices.
     // (.array_chunks() is still in Experimental)
@@ -126,7 +120,9 @@ fn main() {
     // Small sanity-check of the input.
     // No button has a 0 displacement value, which avoids checks for divisions by 0.
     for c in &claw_machines {
-        if c.button_a.0 == 0 || c.button_a.1 == 0 || c.button_b.0 == 0 || c.button_b.1 == 0 {
+        let zero_vector =
+            c.button_a.0 == 0 || c.button_a.1 == 0 || c.button_b.0 == 0 || c.button_b.1 == 0;
+        if zero_vector && !args::quiet() {
             eprintln!("Warning: {:?} has a 0 vector", c);
         }
     }
@@ -4,9 +4,10 @@ https://adventofcode.com/2024/day/14
  */
 
 //use num::integer::div_rem;
+use aoc::args;
+use aoc::input::parse_signed_pairs;
 use std::io;
 use std::io::prelude::*;
-use std::str::FromStr;
 
 // Sample:
 //const GRID_WIDTH:isize = 11;
@@ -73,7 +74,9 @@ fn count_quadrants(input: &Vec<Robot>, elapse: isize) -> usize {
             quadrant_d += 1;
         }
     }
-    eprintln!("quadrants:     {quadrant_a} * {quadrant_b} * {quadrant_c} * {quadrant_d} ");
+    if args::is_debug() {
+        eprintln!("quadrants:     {quadrant_a} * {quadrant_b} * {quadrant_c} * {quadrant_d} ");
+    }
     quadrant_a * quadrant_b * quadrant_c * quadrant_d
 }
 
@@ -139,26 +142,22 @@ fn display_evolution(input: &Vec<Robot>, elapse: isize) {
 fn main() {
     let mut robots = Vec::<Robot>::new();
 
-    // split on space, then on '=', then on ','.
-    // (easier/faster or not than a 4 group regex ?)
     //p=27,64 v=24,1
 
     let mut lines = io::stdin().lock().lines();
     while let Some(Ok(line)) = lines.next() {
-        let pv = line.split_once(" ").unwrap();
-        let p = pv.0.split_once('=').unwrap().1;
-        let v = pv.1.split_once('=').unwrap().1;
-        let p = p.split_once(',').unwrap();
-        let v = v.split_once(',').unwrap();
-        let robot = Robot {
-            p: (isize::from_str(p.0).unwrap(), isize::from_str(p.1).unwrap()),
-            v: (isize::from_str(v.0).unwrap(), isize::from_str(v.1).unwrap()),
-        };
+        let pv = parse_signed_pairs(&line);
+        if pv.len() != 2 {
+            panic!("Robot format error: {line}");
+        }
+        let robot = Robot { p: pv[0], v: pv[1] };
 
         robots.push(robot);
     }
 
-    println!("Part 1 = {}", count_quadrants(&robots, 100));
+    // Number of seconds can be changed with --steps=N
+    let seconds = args::arg_value("steps").unwrap_or(100);
+    println!("Part 1 = {}", count_quadrants(&robots, seconds));
 
     // Actually part 2 is not "do it 1 billion time" at all...
     // But it was near 8000.
@@ -5,6 +5,7 @@ https://adventofcode.com/2024/day/15
 use aoc::args;
 use aoc::colors::*;
 use aoc::grid::{Grid, GridBuilder};
+use aoc::parse::parse_map_and_moves;
 use std::io;
 use std::io::prelude::*;
 
@@ -39,57 +40,215 @@ impl Movement {
     }
 }
 
-fn move_once(
-    robot: (isize, isize),
-    m: Movement,
-    maze: &Grid<bool>,
-    boxes: &mut Grid<bool>,
-) -> (isize, isize) {
-    let d = m.as_delta();
-    let next_pos = (robot.0 + d.0, robot.1 + d.1);
-    // All inputs have walls on the border so we will
-    // never fall into the _or() case of unwrap checking out-of-bound.
-    if boxes.checked_get(next_pos.0, next_pos.1).unwrap_or(true) {
-        // Box in the way. Push it if possible.
-        let mut stack = next_pos;
-        while boxes.checked_get(stack.0, stack.1).unwrap_or(true) {
-            stack = (stack.0 + d.0, stack.1 + d.1);
+/// Rules of how the robot moves in the warehouse and pushes the boxes,
+/// applied one movement at a time by process_all_movements().
+trait PushRules {
+    /// Width and height of the warehouse
+    fn dimensions(&self) -> (usize, usize);
+
+    /// Try to move the robot once, pushing boxes as needed.
+    /// Return the new robot position (unchanged if blocked).
+    fn move_once(&mut self, robot: (isize, isize), m: Movement) -> (isize, isize);
+}
+
+/// Part 1 rules: each box takes a single cell.
+struct SingleBoxes<'a> {
+    maze: &'a Grid<bool>,
+    boxes: &'a mut Grid<bool>,
+}
+
+impl PushRules for SingleBoxes<'_> {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.maze.width, self.maze.height)
+    }
+
+    fn move_once(&mut self, robot: (isize, isize), m: Movement) -> (isize, isize) {
+        let (maze, boxes) = (&self.maze, &mut self.boxes);
+        let d = m.as_delta();
+        let next_pos = (robot.0 + d.0, robot.1 + d.1);
+        // All inputs have walls on the border so we will
+        // never fall into the _or() case of unwrap checking out-of-bound.
+        if boxes.checked_get(next_pos.0, next_pos.1).unwrap_or(true) {
+            // Box in the way. Push it if possible.
+            let mut stack = next_pos;
+            while boxes.checked_get(stack.0, stack.1).unwrap_or(true) {
+                stack = (stack.0 + d.0, stack.1 + d.1);
+            }
+            // End of stack of box
+            if maze.checked_get(stack.0, stack.1).unwrap_or(true) {
+                // Wall in the way. Can't move anything.
+                robot
+            } else {
+                // Free space to push the box stack.
+                // Update head and tail of the stack
+                boxes.set(next_pos.0 as usize, next_pos.1 as usize, false);
+                boxes.set(stack.0 as usize, stack.1 as usize, true);
+                next_pos
+            }
+        } else if maze.checked_get(next_pos.0, next_pos.1).unwrap_or(true) {
+            // Wall in the way. Can't move.
+            robot
+        } else {
+            // Free space. Move.
+            next_pos
         }
-        // End of stack of box
-        if maze.checked_get(stack.0, stack.1).unwrap_or(true) {
-            // Wall in the way. Can't move anything.
+    }
+}
+
+/// Which side of a wide box (part 2) occupies a cell.
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum BoxHalf {
+    Left,
+    Right,
+}
+
+/// Part 2 rules: each box takes two cells, "[]".
+/// Boxes pushed vertically may push two boxes at once, and so on.
+struct WideBoxes<'a> {
+    maze: &'a Grid<bool>,
+    boxes: &'a mut Grid<Option<BoxHalf>>,
+}
+
+impl WideBoxes<'_> {
+    /// Which half of a box is at (x,y), if any.
+    fn box_at(&self, x: isize, y: isize) -> Option<BoxHalf> {
+        self.boxes.checked_get(x, y).flatten()
+    }
+
+    /// Position of the left half of the box at (x,y), if any.
+    fn box_origin(&self, x: isize, y: isize) -> Option<(isize, isize)> {
+        match self.box_at(x, y)? {
+            BoxHalf::Left => Some((x, y)),
+            BoxHalf::Right => Some((x - 1, y)),
+        }
+    }
+
+    /// Other boxes in the way of the box at "origin" moving by "d".
+    /// Return None if a wall is in the way.
+    fn blocking_boxes(
+        &self,
+        origin: (isize, isize),
+        d: (isize, isize),
+    ) -> Option<Vec<(isize, isize)>> {
+        let mut blocking = Vec::new();
+        for x in [origin.0 + d.0, origin.0 + 1 + d.0] {
+            let y = origin.1 + d.1;
+            if self.maze.checked_get(x, y).unwrap_or(true) {
+                return None;
+            }
+            // When pushing horizontally, the box itself is in the way
+            match self.box_origin(x, y) {
+                Some(other) if other != origin && !blocking.contains(&other) => {
+                    blocking.push(other)
+                }
+                _ => {}
+            }
+        }
+        Some(blocking)
+    }
+
+    fn can_push(&self, origin: (isize, isize), d: (isize, isize)) -> bool {
+        match self.blocking_boxes(origin, d) {
+            None => false,
+            Some(blocking) => blocking.into_iter().all(|b| self.can_push(b, d)),
+        }
+    }
+
+    /// Push the box at "origin" and all the boxes in its way.
+    /// can_push() must have been checked first.
+    fn push(&mut self, origin: (isize, isize), d: (isize, isize)) {
+        for b in self.blocking_boxes(origin, d).unwrap() {
+            // may have been already moved by a previous push
+            if self.box_origin(b.0, b.1) == Some(b) {
+                self.push(b, d);
+            }
+        }
+        let (x, y) = (origin.0 as usize, origin.1 as usize);
+        self.boxes.set(x, y, None);
+        self.boxes.set(x + 1, y, None);
+        let (x, y) = ((origin.0 + d.0) as usize, (origin.1 + d.1) as usize);
+        self.boxes.set(x, y, Some(BoxHalf::Left));
+        self.boxes.set(x + 1, y, Some(BoxHalf::Right));
+    }
+}
+
+impl PushRules for WideBoxes<'_> {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.maze.width, self.maze.height)
+    }
+
+    fn move_once(&mut self, robot: (isize, isize), m: Movement) -> (isize, isize) {
+        let d = m.as_delta();
+        let next_pos = (robot.0 + d.0, robot.1 + d.1);
+        if self
+            .maze
+            .checked_get(next_pos.0, next_pos.1)
+            .unwrap_or(true)
+        {
+            // Wall in the way. Can't move.
             robot
+        } else if let Some(origin) = self.box_origin(next_pos.0, next_pos.1) {
+            // Box in the way. Push it (and the ones behind) if possible.
+            if self.can_push(origin, d) {
+                self.push(origin, d);
+                next_pos
+            } else {
+                robot
+            }
         } else {
-            // Free space to push the box stack.
-            // Update head and tail of the stack
-            boxes.set(next_pos.0 as usize, next_pos.1 as usize, false);
-            boxes.set(stack.0 as usize, stack.1 as usize, true);
+            // Free space. Move.
             next_pos
         }
-    } else if maze.checked_get(next_pos.0, next_pos.1).unwrap_or(true) {
-        // Wall in the way. Can't move.
-        robot
-    } else {
-        // Free space. Move.
-        next_pos
     }
 }
 
-// Process each robot movement, pushing as needed.
+// Double the width of a map line for part 2
+fn widen_line(line: &str) -> String {
+    line.chars()
+        .map(|c| match c {
+            '#' => "##",
+            'O' => "[]",
+            '@' => "@.",
+            _ => "..",
+        })
+        .collect()
+}
+
+// Build the part 2 wall and box maps from the original map lines
+fn wide_warehouse(map_lines: &[String]) -> (Grid<bool>, Grid<Option<BoxHalf>>) {
+    let mut mazebuild = GridBuilder::<bool>::new();
+    let mut boxbuild = GridBuilder::<Option<BoxHalf>>::new();
+    for line in map_lines {
+        let line = widen_line(line);
+        mazebuild.append_char_map(&line, '#');
+        let halves: Vec<Option<BoxHalf>> = line
+            .chars()
+            .map(|c| match c {
+                '[' => Some(BoxHalf::Left),
+                ']' => Some(BoxHalf::Right),
+                _ => None,
+            })
+            .collect();
+        boxbuild.append_line(&halves);
+    }
+    (mazebuild.to_grid(), boxbuild.to_grid())
+}
+
+// Process each robot movement, pushing as needed by the rules.
 // Return the final robot position, as well as a trace
 // of all its visited locations in a new grid.
 fn process_all_movements(
     robot: (usize, usize),
     moves: &[Movement],
-    maze: &Grid<bool>,
-    boxes: &mut Grid<bool>,
+    rules: &mut impl PushRules,
 ) -> ((usize, usize), Grid<bool>) {
-    let mut trace = Grid::<bool>::new(maze.width, maze.height, false);
+    let (width, height) = rules.dimensions();
+    let mut trace = Grid::<bool>::new(width, height, false);
     trace.set(robot.0, robot.1, true);
     let mut robot: (isize, isize) = (robot.0 as isize, robot.1 as isize);
 
     for m in moves {
-        robot = move_once(robot, *m, maze, boxes);
+        robot = rules.move_once(robot, *m);
         trace.set(robot.0 as usize, robot.1 as usize, true);
     }
 
@@ -141,23 +300,19 @@ fn main() {
 
     let mut robot: (usize, usize) = (0, 0);
 
-    let mut lines = io::stdin().lock().lines();
-    let mut y = 0;
+    let lines: Vec<String> = io::stdin().lock().lines().map_while(Result::ok).collect();
+    let (map_lines, move_chars) = parse_map_and_moves(&lines);
 
     // parsing the map
-    while let Some(Ok(line)) = lines.next() {
-        if line.len() == 0 {
-            break;
-        }
+    for (y, line) in map_lines.iter().enumerate() {
         if robot == (0, 0) {
             let vs: Vec<char> = line.chars().collect();
             if let Some(s) = vs.iter().position(|&c| c == '@') {
                 robot = (s, y);
             }
         }
-        mazebuild.append_char_map(&line, '#');
-        boxbuild.append_char_map(&line, 'O');
-        y += 1;
+        mazebuild.append_char_map(line, '#');
+        boxbuild.append_char_map(line, 'O');
     }
 
     let maze = mazebuild.to_grid();
@@ -166,18 +321,19 @@ fn main() {
     assert_eq!(maze.height, boxes.height);
 
     // parsing the movements
-    let mut moves = Vec::<Movement>::new();
-    while let Some(Ok(line)) = lines.next() {
-        let mut m: Vec<Movement> = line.chars().map(|c| Movement::from_char(c)).collect();
-        moves.append(&mut m);
-    }
+    let moves: Vec<Movement> = move_chars.chars().map(Movement::from_char).collect();
 
     // Debug print
     if args::is_debug() {
         print_maze(robot, &maze, &boxes)
     }
 
-    let (robot, trace) = process_all_movements(robot, &moves, &maze, &mut boxes);
+    let robot_start = robot;
+    let mut rules = SingleBoxes {
+        maze: &maze,
+        boxes: &mut boxes,
+    };
+    let (robot, trace) = process_all_movements(robot, &moves, &mut rules);
 
     if args::is_debug() {
         trace.pretty_print_bool();
@@ -187,4 +343,95 @@ fn main() {
     let gps_total = sum_gps_coordinates(&boxes);
 
     println!("Part 1 = {gps_total}");
+
+    let (wide_maze, mut wide_boxes) = wide_warehouse(&map_lines);
+    let mut rules = WideBoxes {
+        maze: &wide_maze,
+        boxes: &mut wide_boxes,
+    };
+    let (robot, _) = process_all_movements((robot_start.0 * 2, robot_start.1), &moves, &mut rules);
+
+    // GPS coordinates are measured from the left half of the boxes
+    let left_halves = wide_boxes.map(|b| *b == Some(BoxHalf::Left));
+    if args::is_debug() {
+        print_maze(robot, &wide_maze, &left_halves);
+    }
+
+    let gps_total = sum_gps_coordinates(&left_halves);
+
+    println!("Part 2 = {gps_total}");
+}
+
+#[test]
+fn check_single_boxes_moves() {
+    let warehouse = "########\n\
+                     #..O.O.#\n\
+                     ##@.O..#\n\
+                     #...O..#\n\
+                     #.#.O..#\n\
+                     #...O..#\n\
+                     #......#\n\
+                     ########";
+    let warehouse = aoc::testutil::grid_from_str(warehouse);
+    let maze = warehouse.to_bool('#');
+    let mut boxes = warehouse.to_bool('O');
+    let moves: Vec<Movement> = "<^^>>>vv<v>>v<<".chars().map(Movement::from_char).collect();
+
+    let mut rules = SingleBoxes {
+        maze: &maze,
+        boxes: &mut boxes,
+    };
+    let (robot, trace) = process_all_movements((2, 2), &moves, &mut rules);
+
+    assert_eq!(robot, (4, 4));
+    assert!(trace.get(2, 1));
+    assert!(!trace.get(1, 1));
+    assert_eq!(
+        boxes.true_coords(),
+        vec![(5, 1), (6, 1), (6, 3), (3, 4), (4, 5), (4, 6)]
+    );
+    assert_eq!(sum_gps_coordinates(&boxes), 2028);
+}
+
+#[test]
+fn check_wide_box_halves() {
+    let lines = vec!["#####".to_string(), "#.O.#".to_string()];
+    let (maze, mut boxes) = wide_warehouse(&lines);
+    let rules = WideBoxes {
+        maze: &maze,
+        boxes: &mut boxes,
+    };
+
+    assert_eq!(rules.box_at(4, 1), Some(BoxHalf::Left));
+    assert_eq!(rules.box_at(5, 1), Some(BoxHalf::Right));
+    assert_eq!(rules.box_at(3, 1), None);
+    assert_eq!(rules.box_at(-1, 1), None);
+    // either half identifies the same box
+    assert_eq!(rules.box_origin(4, 1), Some((4, 1)));
+    assert_eq!(rules.box_origin(5, 1), Some((4, 1)));
+}
+
+#[test]
+fn check_wide_boxes_moves() {
+    let lines: Vec<String> = include_str!("../sample.txt")
+        .lines()
+        .map(String::from)
+        .collect();
+    let (map_lines, move_chars) = parse_map_and_moves(&lines);
+    let moves: Vec<Movement> = move_chars.chars().map(Movement::from_char).collect();
+    let robot = map_lines
+        .iter()
+        .enumerate()
+        .find_map(|(y, l)| l.find('@').map(|x| (x * 2, y)))
+        .unwrap();
+    let (maze, mut boxes) = wide_warehouse(&map_lines);
+
+    let mut rules = WideBoxes {
+        maze: &maze,
+        boxes: &mut boxes,
+    };
+    process_all_movements(robot, &moves, &mut rules);
+
+    let left_halves = boxes.map(|b| *b == Some(BoxHalf::Left));
+    assert_eq!(sum_gps_coordinates(&left_halves), 9021);
 }
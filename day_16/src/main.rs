@@ -6,23 +6,41 @@ https://adventofcode.com/2024/day/16
 use aoc::colors::*;
 use aoc::dijkstra::*;
 use aoc::grid::{Grid, GridBuilder};
+use std::collections::HashMap;
 use std::io;
 use std::io::prelude::*;
 use std::time::{Duration, Instant};
 
+// The cost of a move is a number of (turns, steps), made explicit
+// instead of directly adding up the scores.
+type Cost = (usize /*turns*/, usize /*steps*/);
+
+const STEP: Cost = (0, 1);
+const TURN: Cost = (1, 0);
+const FREE: Cost = (0, 0);
+
+// The single distance used by dijkstra: turning is weighted as
+// 1000 steps, so the order of total costs is the order of scores.
+fn score(cost: Cost) -> usize {
+    cost.0 * 1000 + cost.1
+}
+
 #[derive(Clone)]
 struct Maze {
     // Original, read-only map of the input data
     map: Grid<bool>,
     // tuple of (distance, (prev-coordinate))
     path: Grid<(usize, Option<(usize, usize)>)>,
-    // Used to inverse the start/target when solving dijkstra
-    normal_direction: bool,
+    // Minimal distance of each visited node, including its direction.
+    distances: HashMap<(usize, usize, Direction), usize>,
+    // When reversed, dijkstra starts from the target and follows the
+    // edges backward, to find the distance from each node to the target.
+    reversed: bool,
     real_start: (usize, usize, Direction),
     real_target: (usize, usize, Direction),
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 enum Direction {
     Up,
     Left,
@@ -39,13 +57,18 @@ impl Direction {
             Right => (1, 0),
         }
     }
+
+    // The two directions after a 90° rotation
+    fn rotations(&self) -> [Direction; 2] {
+        match self {
+            Up | Down => [Left, Right],
+            Left | Right => [Up, Down],
+        }
+    }
 }
 use Direction::*;
 
 impl Maze {
-    const CONTINUE_FRONT: usize = 1;
-    const ROTATE_90: usize = 1000;
-
     fn new_from_map(map: &Grid<bool>) -> Self {
         let (width, height) = (map.width, map.height);
         // bottom-left +1 corner, facing east
@@ -56,15 +79,76 @@ impl Maze {
         Maze {
             map: map.clone(),
             path: Grid::<(usize, Option<(usize, usize)>)>::new(width, height, (0, None)),
-            normal_direction: true,
+            distances: HashMap::new(),
+            reversed: false,
             real_start,
             real_target,
         }
     }
 
-    fn set_direction(&mut self, direct: bool) {
-        self.normal_direction = direct;
+    fn set_reversed(&mut self, reversed: bool) {
+        self.reversed = reversed;
         self.path.fill((0, None));
+        self.distances.clear();
+    }
+
+    fn is_target_tile(&self, x: usize, y: usize) -> bool {
+        (x, y) == (self.real_target.0, self.real_target.1)
+    }
+
+    fn is_free(&self, x: isize, y: isize) -> bool {
+        self.map.checked_get(x, y) == Some(false)
+    }
+
+    // The possible neighbors are the next node in front of the current direction
+    // (if no wall obstructs) and the two 90° rotation at same x,y.
+    // Special case when we are already at the "end tile" but not in the right direction:
+    // provide 0-cost transition to the target node.
+    fn forward_moves(
+        &self,
+        node: &(usize, usize, Direction),
+    ) -> Vec<((usize, usize, Direction), Cost)> {
+        let (x, y, direction) = *node;
+        if self.is_target_tile(x, y) {
+            return vec![(self.real_target, FREE)];
+        }
+
+        let mut moves = Vec::with_capacity(3);
+        let delta = direction.as_delta();
+        let n = (x as isize + delta.0, y as isize + delta.1);
+        if self.is_free(n.0, n.1) {
+            moves.push(((n.0 as usize, n.1 as usize, direction), STEP));
+        }
+        for r in direction.rotations() {
+            moves.push(((x, y, r), TURN));
+        }
+        moves
+    }
+
+    // Exact reverse of forward_moves(): all the nodes that can move into "node".
+    fn backward_moves(
+        &self,
+        node: &(usize, usize, Direction),
+    ) -> Vec<((usize, usize, Direction), Cost)> {
+        let (x, y, direction) = *node;
+        let mut moves = Vec::with_capacity(4);
+        if *node == self.real_target {
+            for d in [Up, Left, Down, Right] {
+                moves.push(((x, y, d), FREE));
+            }
+        }
+
+        let delta = direction.as_delta();
+        let p = (x as isize - delta.0, y as isize - delta.1);
+        if self.is_free(p.0, p.1) && !self.is_target_tile(p.0 as usize, p.1 as usize) {
+            moves.push(((p.0 as usize, p.1 as usize, direction), STEP));
+        }
+        if !self.is_target_tile(x, y) {
+            for r in direction.rotations() {
+                moves.push(((x, y, r), TURN));
+            }
+        }
+        moves
     }
 }
 
@@ -73,58 +157,28 @@ impl DijkstraController for Maze {
     type Node = (usize, usize, Direction);
 
     fn get_starting_node(&self) -> Self::Node {
-        if self.normal_direction {
-            self.real_start
-        } else {
+        if self.reversed {
             self.real_target
+        } else {
+            self.real_start
         }
     }
 
     fn get_target_node(&self) -> Self::Node {
-        if self.normal_direction {
-            self.real_target
-        } else {
+        if self.reversed {
             self.real_start
+        } else {
+            self.real_target
         }
     }
 
-    // The possible neighbors are the next node in front of the current direction
-    // (if no wall obstructs) and the two 90° rotation at same x,y.
     fn get_neighbors_distances(&self, node: &Self::Node) -> Vec<(Self::Node, usize)> {
-        let mut neighbs = Vec::<(Self::Node, usize)>::with_capacity(3);
-
-        // Special case when we are already at the "end node" but not in the right direction:
-        // provide 0-cost transition
-        let target = self.get_target_node();
-        if node.0 == target.0 && node.1 == target.1 {
-            neighbs.push((target, 0));
-            return neighbs;
-        }
-
-        let signed_node: (isize, isize) = (node.0 as isize, node.1 as isize);
-        let direction = node.2;
-        let delta = direction.as_delta();
-        // Continue same direction, no wall in front
-        let n = (signed_node.0 + delta.0, signed_node.1 + delta.1);
-        if let Some(is_wall) = self.map.checked_get(n.0, n.1) {
-            if !is_wall {
-                let nnode = (n.0 as usize, n.1 as usize, direction);
-                neighbs.push((nnode, Self::CONTINUE_FRONT));
-            }
-        }
-        // Two possible rotations on place
-        match direction {
-            Up | Down => {
-                neighbs.push(((node.0, node.1, Left), Self::ROTATE_90));
-                neighbs.push(((node.0, node.1, Right), Self::ROTATE_90));
-            }
-            Left | Right => {
-                neighbs.push(((node.0, node.1, Up), Self::ROTATE_90));
-                neighbs.push(((node.0, node.1, Down), Self::ROTATE_90));
-            }
-        }
-
-        neighbs
+        let moves = if self.reversed {
+            self.backward_moves(node)
+        } else {
+            self.forward_moves(node)
+        };
+        moves.into_iter().map(|(n, c)| (n, score(c))).collect()
     }
 
     fn mark_visited_distance(
@@ -133,6 +187,8 @@ impl DijkstraController for Maze {
         distance: usize,
         previous: Option<Self::Node>,
     ) {
+        self.distances.insert(node, distance);
+
         // Only mark a node the first time it is visited;
         // So this will mark a (x,y) only when coming from
         // a different cell coordinates, and not when rotating
@@ -150,6 +206,42 @@ impl DijkstraController for Maze {
     }
 }
 
+// Return the best score (part 1), the map of all the tiles on any
+// of the best paths (part 2), and the maze state after the part 1 search.
+//
+// Part 2: a node (x,y,direction) is on a best path if its distance
+// from the start plus its distance to the target equals the best score.
+// The second distance is found by a reversed search from the target.
+// Distances are compared on the full nodes including the direction,
+// as the cost of the rotations makes it impossible on the tiles only.
+fn best_paths(map: &Grid<bool>) -> (usize, Grid<bool>, Maze) {
+    let mut forward = Maze::new_from_map(map);
+    // For part 1 we need only 1 path, but to prepare for part 2
+    // ask right now to also explore all possible tiles.
+    let best = dijkstra(&mut forward, true);
+
+    let mut backward = forward.clone();
+    backward.set_reversed(true);
+    dijkstra(&mut backward, true);
+
+    let mut tiles = Grid::<bool>::new(map.width, map.height, false);
+    for (node, d) in &forward.distances {
+        if let Some(d2) = backward.distances.get(node) {
+            if d + d2 == best {
+                tiles.set(node.0, node.1, true);
+            }
+        }
+    }
+
+    (best, tiles, forward)
+}
+
+fn count_tiles(tiles: &Grid<bool>) -> usize {
+    (0..tiles.height)
+        .map(|y| (0..tiles.width).filter(|x| tiles.get(*x, y)).count())
+        .sum()
+}
+
 // Used only for pretty-printing debug
 fn fill_backward_path(
     start: <Maze as DijkstraController>::Node,
@@ -192,75 +284,14 @@ fn main() {
 
     let map = gb.to_grid();
     let (width, height) = (map.width, map.height);
-    let mut graph = Maze::new_from_map(&map);
     let elapsed_parse: Duration = Instant::now() - start_parse; // Calculate elapsed time.
 
     // ----
     let start_process = Instant::now(); // Start measuring time.
 
-    // For part 1 we need only 1 path, but to prepare for part 2
-    // ask right now to also explore all possible tiles.
-    let distance = dijkstra(&mut graph, true);
+    let (distance, added_path, graph) = best_paths(&map);
     println!("Part 1 = {}", distance);
-
-    /* Part 2 technique
-     * it may be just an heuristic that doesn't work on all cases, but here:
-     * We search for all distance from "starting" or "target" tile;
-     * when a tile sum of those distances is equal to the total found best distance,
-     * we consider that it's an element of two half-paths that "meet on the middle".
-     * - It works... barely, as strict equality gives only the "corners" of paths;
-     *   by adding 1000 (the cost of turning), it also finds the straight tiles between
-     *   turns.
-     * - It finds too many tiles: in sample 2, there is one additional (wrong) path
-     * - It misses exactly 1 tile for the problem input, somewhere in the middle of a straight
-     *   path on the first 1/3.  (bug visually found by the pretty_print output, corrected
-     *   by submitting one more on the form...)
-     *
-     * Things also arbitrarily change if modifying the default of the Target node.
-     *
-     * The real problem is that we use the distance stored in Grid<> which lacks the
-     * "rotation" dimension, instead of directly using the virtual graph of nodes
-     * used by the real dijkstra algo. Entering, exiting or rotating inside a node adds distance
-     * information that is not present in the graph.path final data.
-     */
-
-    // Keep a backup copy of the part 1 distance map.
-    let mut graph2 = graph.clone();
-
-    // Reverse the search to map the maze in the other way
-    graph2.set_direction(false);
-    // Note: the reversed distance could be different than the one in part1,
-    // as the reversed target/starting node is oriented arbitrarily and
-    // requires one more rotation for a score + 1000  (in sample 2)
-    let distance2 = dijkstra(&mut graph2, true);
-    //eprintln!("Part 2 distance = {}", distance2);
-
-    let testdist = if distance2 > distance {
-        distance2
-    } else {
-        distance
-    };
-
-    let part1 = &graph.path;
-    let part2 = &graph2.path;
-
-    let mut added_path = Grid::<bool>::new(part2.width, part2.height, false);
-
-    let mut total_best_tiles = 0;
-
-    for x in 0..part1.width {
-        for y in 0..part1.height {
-            let n1 = part1.get(x, y);
-            let n2 = part2.get(x, y);
-            let summed = n1.0 + n2.0;
-            if summed == testdist || summed == testdist + 1000 {
-                total_best_tiles += 1;
-                added_path.set(x, y, true);
-            }
-        }
-    }
-
-    println!("Part 2 = {} (more or less)", total_best_tiles);
+    println!("Part 2 = {}", count_tiles(&added_path));
 
     let elapsed_process: Duration = Instant::now() - start_process; // Calculate elapsed time.
 
@@ -328,7 +359,70 @@ fn main() {
         eprintln!("Time taken for debug print: {:?}", elapsed_print);
     } // is_debug
 
-    eprintln!("Time taken for parsing: {:?}", elapsed_parse);
-    eprintln!("Time taken for processing: {:?}", elapsed_process);
-    eprintln!("Total time: {:?}", elapsed_process + elapsed_parse);
+    if !aoc::args::quiet() {
+        eprintln!("Time taken for parsing: {:?}", elapsed_parse);
+        eprintln!("Time taken for processing: {:?}", elapsed_process);
+        eprintln!("Total time: {:?}", elapsed_process + elapsed_parse);
+    }
+}
+
+#[cfg(test)]
+fn parse_maze(rows: &[&str]) -> Grid<bool> {
+    let mut gb = GridBuilder::<bool>::new();
+    for row in rows {
+        gb.append_char_map(row, '#');
+    }
+    gb.to_grid()
+}
+
+#[test]
+fn check_sample_best_paths() {
+    let map = parse_maze(&[
+        "###############",
+        "#.......#....E#",
+        "#.#.###.#.###.#",
+        "#.....#.#...#.#",
+        "#.###.#####.#.#",
+        "#.#.#.......#.#",
+        "#.#.#####.###.#",
+        "#...........#.#",
+        "###.#.#####.#.#",
+        "#...#.....#.#.#",
+        "#.#.#.###.#.#.#",
+        "#.....#...#.#.#",
+        "#.###.#.#.#.#.#",
+        "#S..#.....#...#",
+        "###############",
+    ]);
+    let (best, tiles, _) = best_paths(&map);
+    assert_eq!(best, 7036);
+    assert_eq!(score((7, 36)), 7036);
+    assert_eq!(count_tiles(&tiles), 45);
+}
+
+#[test]
+fn check_sample2_best_paths() {
+    // The heuristic on tiles distances found 77 instead of 64 here.
+    let map = parse_maze(&[
+        "#################",
+        "#...#...#...#..E#",
+        "#.#.#.#.#.#.#.#.#",
+        "#.#.#.#...#...#.#",
+        "#.#.#.#.###.#.#.#",
+        "#...#.#.#.....#.#",
+        "#.#.#.#.#.#####.#",
+        "#.#...#.#.#.....#",
+        "#.#.#####.#.###.#",
+        "#.#.#.......#...#",
+        "#.#.###.#####.###",
+        "#.#.#...#.....#.#",
+        "#.#.#.#####.###.#",
+        "#.#.#.........#.#",
+        "#.#.#.#########.#",
+        "#S#.............#",
+        "#################",
+    ]);
+    let (best, tiles, _) = best_paths(&map);
+    assert_eq!(best, 11048);
+    assert_eq!(count_tiles(&tiles), 64);
 }
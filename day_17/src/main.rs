@@ -3,292 +3,119 @@ https://adventofcode.com/2024/day/17
 --- Day 17: Chronospatial Computer ---
  */
 
+use aoc::vm::{self, Machine};
 use std::collections::HashSet;
 use std::io;
 use std::io::prelude::*;
-use std::str::FromStr;
 
-#[derive(Clone)]
-struct Machine {
-    instruction_ptr: usize,
-    register_a: usize,
-    register_b: usize,
-    register_c: usize,
-    program: Vec<u8>,
-    output: Vec<u8>,
-}
-
-#[derive(Copy, Clone)]
-struct ComboOperand(u8);
-#[derive(Copy, Clone)]
-struct LiteralOperand(u8);
-
-/// Each possible machine instruction with their embedded operand type.
-enum Instruction {
-    Adv(ComboOperand),
-    Bxl(LiteralOperand),
-    Bst(ComboOperand),
-    Jnz(LiteralOperand),
-    Bxc,
-    Out(ComboOperand),
-    Bdv(ComboOperand),
-    Cdv(ComboOperand),
+fn brute_force(machine: &mut Machine) {
+    let lower_a = 1 << (machine.program.len() - 1) * 3;
+    let higher_a = 2 << (machine.program.len() - 1) * 3;
+    println!("Range : {lower_a} .. {higher_a}");
+    for k in lower_a..higher_a {
+        if k % 100000 == 0 && aoc::args::is_debug() {
+            eprintln!("testing {k}...");
+        }
+        machine.reset_with_register(k);
+        if machine.run_until_halt_or_non_quine() {
+            machine.print_output();
+            println!("Part2 : Register A value for Quine = {k}");
+            break;
+        }
+    }
 }
 
-use Instruction::*;
-
-impl Machine {
-    /// Run the program from its current state/IP until it halts.
-    fn run_until_halt(&mut self) {
-        while self.execute_one_step() {}
-    }
+// Limit for the widening of the search window, as each
+// additional bit doubles the number of candidates tested.
+const MAX_LOW_BITS: usize = 16;
+
+/// Construct, digit by digit, the values of register A making the
+/// program output itself, and return the smallest one.
+/// For each new digit, "low_bits" more bits of A are tried above the
+/// bits already fixed for the previous digits; if no candidate is found
+/// the window is widened by one bit and the digit is searched again,
+/// up to MAX_LOW_BITS.
+fn find_quine_register(machine: &Machine, low_bits: usize) -> Option<usize> {
+    let mut machine = machine.clone();
+    let program_len = machine.program.len();
+
+    // Just to avoid special_casing the first digit
+    let mut valid_a = vec![0];
+    let mut range_factor = 1;
+    let mut bits = low_bits;
 
-    /// Run the program. If the output is different
-    /// than the program code, stops and return false.
-    /// If it halts and output == program, return true.
-    fn run_until_halt_or_non_quine(&mut self) -> bool {
-        self.run_until_halt_or_non_quine_or_outlen(self.program.len() + 1)
-    }
+    let mut digit = 1;
+    while digit <= program_len {
+        // the set sum { valid + k }  leads to many duplicates,
+        // this hashset speeds up iteration from 1min15 to 7s.
+        let mut checked = HashSet::<usize>::new();
+        let mut next_valid_a = Vec::<usize>::new();
 
-    /// Run the program. If the output is different
-    /// than the program code, stops and return false.
-    /// If it halts, or the output length reached the
-    /// specified size, and output == program, return true.
-    fn run_until_halt_or_non_quine_or_outlen(&mut self, maxlen: usize) -> bool {
-        // Compare only new "out" elements (no need
-        // to compare the full array every time)
-        let mut checked_len = 0;
-        loop {
-            let halted = !self.execute_one_step();
-            let out_len = self.output.len();
-            if out_len > checked_len {
-                if out_len > self.program.len() {
-                    // output longer than program
-                    //println!("output too long");
-                    //self.print_output();
-                    return false;
+        for prev in &valid_a {
+            for k in 0..1 << bits {
+                let a = *prev + k * range_factor;
+                if !checked.insert(a) {
+                    // this value was already tested.
+                    continue;
                 }
-                if self.output[out_len - 1] != self.program[out_len - 1] {
-                    // latest element differs
-                    //println!("output differs at end");
-                    //self.print_output();
-                    return false;
+
+                machine.reset_with_register(a);
+                let quine = if digit == program_len {
+                    // For final loop we require exact match, not a prefix
+                    // that continues for longer.
+                    machine.run_until_halt_or_non_quine()
+                } else {
+                    machine.run_until_halt_or_non_quine_or_outlen(digit)
+                };
+                if quine {
+                    if aoc::args::is_debug() {
+                        eprintln!(
+                            "Partial found : First {digit} matching characters found for A = {a}"
+                        );
+                    }
+                    next_valid_a.push(a);
                 }
-                checked_len = out_len;
-            }
-            if out_len == maxlen {
-                return true;
             }
-            if halted {
-                //self.print_output();
-                return self.program.len() == self.output.len();
-            }
-        }
-    }
-
-    /// Reboots the machine with a specific starting register value.
-    fn reset_with_register(&mut self, a: usize) {
-        self.instruction_ptr = 0;
-        self.register_a = a;
-        self.register_b = 0;
-        self.register_c = 0;
-        self.output.truncate(0);
-    }
-
-    /// Run the program for exactly one instruction
-    /// (at current IP).
-    /// Returns true if it executed,
-    /// false if it is now halted.
-    fn execute_one_step(&mut self) -> bool {
-        if self.instruction_ptr >= self.program.len() {
-            return false;
         }
 
-        let ins = self.decode_current_instruction();
-        self.instruction_ptr += 2;
-
-        match ins {
-            // Div A by power of 2 (= bit shift), multiple register dest
-            Adv(d) | Bdv(d) | Cdv(d) => {
-                let res = self.register_a >> self.get_combo_value(d);
-                match ins {
-                    Adv(_) => self.register_a = res,
-                    Bdv(_) => self.register_b = res,
-                    Cdv(_) => self.register_c = res,
-                    // rustc should know that we can match only on the first ones ?
-                    _ => panic!("Impossible inner match"),
-                }
+        if next_valid_a.is_empty() {
+            if bits >= MAX_LOW_BITS {
+                return None;
             }
-            // bitwise xor
-            Bxl(x) => self.register_b ^= x.0 as usize,
-            // modulo 8
-            Bst(v) => self.register_b = self.get_combo_value(v) % 8,
-            // cond jump if A != 0
-            Jnz(p) => {
-                if self.register_a != 0 {
-                    self.instruction_ptr = p.0 as usize;
-                }
+            bits += 1;
+            if !aoc::args::quiet() {
+                eprintln!("No candidate A found to match the first {digit} output, widening search to {bits} bits");
             }
-            // Xor C into B
-            Bxc => self.register_b ^= self.register_c,
-            // out
-            Out(o) => self.output.push((self.get_combo_value(o) % 8) as u8),
+            continue;
         }
 
-        true
-    }
-
-    /// Return the Instruction encoded at current IP
-    fn decode_current_instruction(&self) -> Instruction {
-        self.decode_instruction_at(self.instruction_ptr)
-    }
-
-    /// Return the Instruction encoded at any valid program offset
-    fn decode_instruction_at(&self, p: usize) -> Instruction {
-        let i = self.program[p];
-        let o = self.program[p + 1];
-
-        match i {
-            0 => Adv(ComboOperand(o)),
-            1 => Bxl(LiteralOperand(o)),
-            2 => Bst(ComboOperand(o)),
-            3 => Jnz(LiteralOperand(o)),
-            4 => Bxc,
-            5 => Out(ComboOperand(o)),
-            6 => Bdv(ComboOperand(o)),
-            7 => Cdv(ComboOperand(o)),
-            _ => panic!("Illegal instruction {i}"),
-        }
-    }
-
-    /// Make the necessary indirection from a combo operand
-    /// encoding into the real value/register value.
-    fn get_combo_value(&self, o: ComboOperand) -> usize {
-        match o.0 {
-            0..=3 => o.0.into(),
-            4 => self.register_a,
-            5 => self.register_b,
-            6 => self.register_c,
-            _ => panic!("Illegal combo operand {}", o.0),
-        }
-    }
-
-    /// Format the output vector with coma separator
-    fn print_output(&self) {
-        let s: String = self
-            .output
-            .iter()
-            .map(|v| v.to_string())
-            .collect::<Vec<String>>()
-            .join(",");
-        println!("Program output:");
-        println!("{s}");
-    }
-
-    fn get_combo_representation(o: ComboOperand) -> String {
-        match o.0 {
-            0..=3 => o.0.to_string(),
-            4 => String::from("A"),
-            5 => String::from("B"),
-            6 => String::from("C"),
-            _ => String::from("???"),
-        }
-    }
-
-    /// For debug: output readable assembly
-    fn pretty_print_assembly(&self) {
-        for k in (0..self.program.len()).step_by(2) {
-            eprint!("{:02}: ", k);
-            let ins = self.decode_instruction_at(k);
-            match ins {
-                Adv(d) => eprintln!("ADV {}", Self::get_combo_representation(d)),
-                Bdv(d) => eprintln!("BDV {}", Self::get_combo_representation(d)),
-                Cdv(d) => eprintln!("CDV {}", Self::get_combo_representation(d)),
-                Bxl(x) => eprintln!("BXL {}", x.0),
-                Bst(v) => eprintln!("BST {}", Self::get_combo_representation(v)),
-                Jnz(p) => eprintln!("JNZ {}", p.0),
-                // Xor C into B
-                Bxc => eprintln!("BXC"),
-                // out
-                Out(o) => eprintln!("OUT {} % 8", Self::get_combo_representation(o)),
-            }
-        }
-    }
+        // Collect our different candidates for next digit.
+        // the hashset already avoid duplications.
+        valid_a = next_valid_a;
 
-    fn pretty_print_pseudocode(&self) {
-        for k in (0..self.program.len()).step_by(2) {
-            eprint!("{:02}: ", k);
-            let ins = self.decode_instruction_at(k);
-            match ins {
-                Adv(d) => eprintln!("A = A >> {}", Self::get_combo_representation(d)),
-                Bdv(d) => eprintln!("B = A >> {}", Self::get_combo_representation(d)),
-                Cdv(d) => eprintln!("C = A >> {}", Self::get_combo_representation(d)),
-                Bxl(x) => eprintln!("B = B xor {}", x.0),
-                Bst(v) => eprintln!("B = {} % 8", Self::get_combo_representation(v)),
-                Jnz(p) => eprintln!("If A != 0 JMP {}", p.0),
-                Bxc => eprintln!("B = B xor C"),
-                // out
-                Out(o) => eprintln!("OUT {} % 8", Self::get_combo_representation(o)),
-            }
-        }
+        range_factor *= 8;
+        digit += 1;
     }
-}
 
-fn brute_force(machine: &mut Machine) {
-    let lower_a = 1 << (machine.program.len() - 1) * 3;
-    let higher_a = 2 << (machine.program.len() - 1) * 3;
-    println!("Range : {lower_a} .. {higher_a}");
-    for k in lower_a..higher_a {
-        if k % 100000 == 0 {
-            eprintln!("testing {k}...");
-        }
-        machine.reset_with_register(k);
-        if machine.run_until_halt_or_non_quine() {
-            machine.print_output();
-            println!("Part2 : Register A value for Quine = {k}");
-            break;
-        }
-    }
+    valid_a.into_iter().min()
 }
 
 fn main() {
-    let mut machine = Machine {
-        instruction_ptr: 0,
-        register_a: 0,
-        register_b: 0,
-        register_c: 0,
-        program: Vec::<u8>::new(),
-        output: Vec::<u8>::new(),
-    };
-    let mut lines = io::stdin().lock().lines();
-    if let Some(Ok(line)) = lines.next() {
-        let val = line.split_once(": ").unwrap().1;
-        machine.register_a = usize::from_str(val).unwrap();
-    }
-    if let Some(Ok(line)) = lines.next() {
-        let val = line.split_once(": ").unwrap().1;
-        machine.register_b = usize::from_str(val).unwrap();
-    }
-    if let Some(Ok(line)) = lines.next() {
-        let val = line.split_once(": ").unwrap().1;
-        machine.register_c = usize::from_str(val).unwrap();
-    }
-    lines.next(); // empty
-    if let Some(Ok(line)) = lines.next() {
-        let program = line.split_once(": ").unwrap().1;
-        machine.program = program
-            .split(',')
-            .map(|v| u8::from_str(v).unwrap())
-            .collect();
-    }
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .expect("input should be readable");
+    let mut machine = vm::parse(&input);
 
-    println!("============");
-    machine.pretty_print_assembly();
-    println!("============");
-    machine.pretty_print_pseudocode();
-    println!("============");
+    if aoc::args::is_verbose() {
+        println!("============");
+        machine.pretty_print_assembly();
+        println!("============");
+        machine.pretty_print_pseudocode();
+        println!("============");
+    }
 
-    machine.run_until_halt();
+    machine.run();
     println!("Part1:");
     machine.print_output();
 
@@ -350,89 +177,22 @@ fn main() {
     // the second out number will be generated from a slightly
     // modified A, shifted by 3 bits and a few xors.
 
-    let mut valid_a = Vec::<usize>::new();
-
-    valid_a.push(0); // Just to avoid special_casing the first digit
-
-    let mut range_factor = 1;
-    let bits = 8;
-
-    for digit in 1..=machine.program.len() {
-        // the set sum { valid + k }  leads to many duplicates,
-        // this hashset speeds up iteration from 1min15 to 7s.
-        let mut checked = HashSet::<usize>::new();
-        let mut next_valid_a = Vec::<usize>::new();
-
-        for prev in &valid_a {
-            // "512" was too low to actually cover all possible source of
-            // bit modification from A to the output, 1024 covers all 10 bits
-            // and find the correct answer.
-            for k in 0..1024 {
-                let a = *prev + k * range_factor;
-                if !checked.insert(a) {
-                    // this value was already tested.
-                    continue;
-                }
-
-                machine.reset_with_register(a);
-                let quine = if digit == machine.program.len() {
-                    // For final loop we require exact match, not a prefix
-                    // that continues for longer.
-                    machine.run_until_halt_or_non_quine()
-                } else {
-                    machine.run_until_halt_or_non_quine_or_outlen(digit)
-                };
-                if quine {
-                    machine.print_output();
-                    println!("Partial found : First {digit} matching characters found for A = {a}");
-                    next_valid_a.push(a);
-                }
-            }
-        }
-
-        if next_valid_a.len() == 0 {
-            panic!(
-                "No candidate A found to match the first {digit} output ! Must expand search range"
-            );
-        }
-
-        println!("Previous lowest A was {}", valid_a[0]);
-
-        // Collect our different candidates for next digit.
-        // the hashset already avoid duplications.
-        valid_a = next_valid_a;
-
-        range_factor *= bits;
-    }
-
-    valid_a.sort();
-
-    println!("All valid_A = {:?}", valid_a);
-    // We need only the first one, check just a few for display.
-    valid_a.truncate(10);
-
-    println!("Part 2: Valid A for complete quine ?");
-    for a in &valid_a {
-        machine.reset_with_register(*a);
-        if machine.run_until_halt_or_non_quine() {
-            machine.print_output();
-            println!("Found register A = {} == oct {:o}", *a, *a);
-        } else {
-            machine.print_output();
-            println!("register A = {} is invalid !! (bug)", *a);
-        }
-    }
-
-    /* debug */
-    let h = 42567035290;
-    machine.reset_with_register(h);
-    if machine.run_until_halt_or_non_quine() {
-        machine.print_output();
-        println!("Found hardcoded register A = {h}",);
-    } else {
-        println!("hardcoded {h} is not quine:",);
-        machine.print_output();
+    // Number of low bits tried for each new digit, can be changed with --bits=N.
+    // "9" was too low to actually cover all possible source of
+    // bit modification from A to the output for my input, 10 bits
+    // covers it; the search widens by itself anyway if needed.
+    let low_bits = aoc::args::arg_value("bits").unwrap_or(10);
+    match find_quine_register(&machine, low_bits) {
+        Some(a) => println!("Part 2 = {a}"),
+        None => println!("Part 2: no register A found to make a quine"),
     }
+}
 
-    println!("Part 2 : First valid A is {}", valid_a[0]);
+#[test]
+fn check_quine_search() {
+    let machine = vm::parse(include_str!("../sample_quine.txt"));
+    // the default window
+    assert_eq!(find_quine_register(&machine, 10), Some(117440));
+    // too narrow for the second digit, must widen by itself
+    assert_eq!(find_quine_register(&machine, 3), Some(117440));
 }
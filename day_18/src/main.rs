@@ -4,7 +4,8 @@ https://adventofcode.com/2024/day/18
  */
 
 use aoc::dijkstra::*;
-use aoc::grid::{Grid, GridBuilder};
+use aoc::grid::Grid;
+use aoc::search::bisect;
 use std::io;
 use std::io::prelude::*;
 use std::str::FromStr;
@@ -43,17 +44,7 @@ impl Maze {
     }
 
     fn get_bool_map_from_generation(&self) -> Grid<bool> {
-        let mut bool_builder = GridBuilder::<bool>::new();
-        for y in 0..self.map.height {
-            let bools: Vec<bool> = self
-                .map
-                .get_row_slice(y)
-                .iter()
-                .map(|gen| *gen <= self.generation)
-                .collect();
-            bool_builder.append_line(&bools);
-        }
-        bool_builder.to_grid()
+        self.map.threshold(self.generation)
     }
 }
 
@@ -70,20 +61,14 @@ impl DijkstraController for Maze {
     }
 
     fn get_neighbors_distances(&self, node: &Self::Node) -> Vec<(Self::Node, usize)> {
-        let (x, y) = node;
         let mut neighbs = Vec::<(Self::Node, usize)>::with_capacity(4);
-        for (dx, dy) in [(1, 0), (-1, 0), (0, 1), (0, -1)] {
-            match self.map.checked_get(x + dx, y + dy) {
-                None => (),
-                Some(gen) => {
-                    if gen > self.generation {
-                        // If a corrupted byte is created later
-                        // than the current generation we consider,
-                        // it's as if it is not here.
-                        neighbs.push(((x + dx, y + dy), 1));
-                    } else {
-                        ()
-                    }
+        for (nx, ny) in self.map.neighbors4_signed(node.0, node.1) {
+            if let Some(gen) = self.map.checked_get(nx, ny) {
+                // If a corrupted byte is created later
+                // than the current generation we consider,
+                // it's as if it is not here.
+                if gen > self.generation {
+                    neighbs.push(((nx, ny), 1));
                 }
             }
         }
@@ -101,70 +86,34 @@ impl DijkstraController for Maze {
     }
 }
 
-fn main() {
-    // ----
-    let start_parse = Instant::now();
+/// Solve both parts for the list of falling bytes coordinates, on
+/// a square memory space of the given size.
+/// Returns the shortest path length after part1_gen bytes have fallen,
+/// and the coordinates of the first byte blocking all the paths.
+fn solve(coords: &[(usize, usize)], size: usize, part1_gen: usize) -> (usize, (usize, usize)) {
     // To simplify algo, "empty" cells (non corrupted)
     // are represented as "infinite" generation number
     // instead of 0.
     // This way, "cell is free <==> cell > tested_generation"
     // without any special case for 0.
-    let mut map = Grid::<u16>::new(71, 71, u16::MAX);
-    let mut coords = Vec::<(usize, usize)>::new();
-    let mut generation: u16 = 0;
-    let mut max_generation = 12;
-
-    let mut lines = io::stdin().lock().lines();
-    let mut max_coord = 0;
-
-    // Parse the input and fill the map by recording
-    // the order each cell is corrupted ("generation")
-    while let Some(Ok(line)) = lines.next() {
-        if let Some((x, y)) = line.split_once(',') {
-            let x = usize::from_str(x).unwrap();
-            let y = usize::from_str(y).unwrap();
-            generation += 1;
-            map.set(x, y, generation);
-            coords.push((x, y));
-
-            // Distinguish samples and actual prod input,
-            // for different algo parameters
-            max_coord = std::cmp::max(max_coord, x);
-            max_coord = std::cmp::max(max_coord, y);
-            if max_coord > 6 {
-                max_generation = 1024;
-            }
-        } else {
-            panic!("invalid input format {line}");
-        }
+    // Generations are recorded as the order each cell is corrupted
+    // (starting at 1)
+    let mut map = Grid::<u16>::new(size, size, u16::MAX);
+    for (generation, (x, y)) in coords.iter().enumerate() {
+        map.set(*x, *y, generation as u16 + 1);
     }
 
-    if max_coord <= 6 {
-        println!("Using 'sample' small coordinates");
-        let mut map2 = Grid::<u16>::new(7, 7, 0);
-        for x in 0..7 {
-            for y in 0..7 {
-                map2.set(x, y, map.get(x, y));
-            }
-        }
-        map = map2;
-    }
-
-    let elapsed_parse: Duration = Instant::now() - start_parse; // Calculate elapsed time.
-
     // ---- Part 1
-    let start_process = Instant::now(); // Start measuring time.
-
     let mut maze = Maze::new_from_map(&map);
-    maze.set_generation(max_generation);
+    maze.set_generation(part1_gen as u16);
 
-    let distance = dijkstra(&mut maze, false);
+    let distance = bfs(&mut maze);
     if aoc::args::is_verbose() {
         let generation_map = maze.get_bool_map_from_generation();
         generation_map.pretty_print_bool_half();
-        println!("shortest path at generation {max_generation}:");
+        println!("shortest path at generation {part1_gen}:");
         // Reconstruct (one of the possible) shortest path by walking back from the exit
-        // on the finalized nodes set on the dijkstracontroller
+        // on the visited nodes set on the dijkstracontroller
         let mut shortpath = Grid::<bool>::new(maze.map.width, maze.map.height, false);
         let mut walknode = maze.exit;
         while let Some(Some(prevnode)) = maze.paths.checked_get(walknode.0, walknode.1) {
@@ -174,70 +123,106 @@ fn main() {
         shortpath.pretty_print_bool_half();
     }
 
-    println!("Part 1 = {}", distance);
-
     // ---- Part 2
 
-    // Dichotomize the generation to find blocking/non blocking states.
-    // "bisect_prev" is not blocking, and "bisect_next" is blocking.
-    // expect from the problem input that the starting generation (12 or 1024)
-    // is never blocking, and the final generation is always blocking.
-    let mut bisect_prev = max_generation;
-    let mut bisect_next = generation;
-
+    // Bisect the generation to find the first blocking state.
+    // Generation 0 (nothing fallen) is never blocking, and
+    // expect from the problem input that the final generation is always blocking.
     if aoc::args::is_debug() {
         println!(
-            "Starting bisecting to find blocking gen, between {bisect_prev} and {bisect_next}"
+            "Starting bisecting to find blocking gen, up to {}",
+            coords.len()
         );
     }
 
-    while bisect_next > bisect_prev + 1 {
-        let bisect_test;
-        if bisect_next > bisect_prev + 2 {
-            bisect_test = (bisect_prev + bisect_next) / 2;
-        } else {
-            bisect_test = bisect_prev + 1;
-        }
+    let blocking_gen = bisect(0, coords.len(), |test_gen| {
+        maze.set_generation(test_gen as u16);
+        let blocked = bfs(&mut maze) == usize::MAX;
         if aoc::args::is_debug() {
-            println!("bisect: {bisect_test}");
+            if blocked {
+                println!("bisect: Maze was impossible to solve at generation {test_gen}");
+            } else {
+                println!("bisect: Maze was ok to solve at generation {test_gen}");
+            }
         }
-        maze.set_generation(bisect_test);
-        let test_distance = dijkstra(&mut maze, false);
+        blocked
+    });
 
-        if test_distance == usize::MAX {
-            if aoc::args::is_debug() {
-                println!("bisect: Maze was impossible to solve at generation {bisect_test}");
-            }
+    // Found the exact blocking generation
+    let blocking_cell = coords[blocking_gen - 1]; // array is 0-indexed
+    if aoc::args::is_debug() {
+        println!(
+            "Part 2: Maze was blocked on generation {blocking_gen} at cell coordinate {:?}",
+            blocking_cell
+        );
+    }
+
+    (distance, blocking_cell)
+}
+
+fn main() {
+    // ----
+    let start_parse = Instant::now();
+    let mut coords = Vec::<(usize, usize)>::new();
 
-            // Bisect: found "bad"
-            bisect_next = bisect_test;
+    let mut lines = io::stdin().lock().lines();
+    let mut max_coord = 0;
+
+    while let Some(Ok(line)) = lines.next() {
+        if let Some((x, y)) = line.split_once(',') {
+            let x = usize::from_str(x).unwrap();
+            let y = usize::from_str(y).unwrap();
+            coords.push((x, y));
+            max_coord = std::cmp::max(max_coord, x);
+            max_coord = std::cmp::max(max_coord, y);
         } else {
-            if aoc::args::is_debug() {
-                println!("bisect: Maze was ok to solve at generation {bisect_test}");
-            }
-            // Bisect: found "good"
-            bisect_prev = bisect_test;
+            panic!("invalid input format {line}");
         }
     }
 
-    if bisect_next == bisect_prev + 1 {
-        // Found the exact blocking generation
-        let blocking_cell = coords.get((bisect_next - 1) as usize); // array is 0-indexed
-        if aoc::args::is_debug() {
-            println!(
-                "Part 2: Maze was blocked on generation {bisect_next} at cell coordinate {:?}",
-                blocking_cell
-            );
-        }
-        let coordinates = blocking_cell.unwrap();
-        println!("Part 2: {},{}", coordinates.0, coordinates.1);
+    // Distinguish samples and actual prod input,
+    // for different algo parameters
+    let (size, mut part1_generation) = if max_coord <= 6 {
+        println!("Using 'sample' small coordinates");
+        (7, 12)
     } else {
-        panic!("Part2 : bisect didn't converge");
+        (71, 1024)
+    };
+    // The generation of part 1 can be changed with --steps=N
+    if let Some(steps) = aoc::args::arg_value("steps") {
+        part1_generation = steps;
     }
 
+    let elapsed_parse: Duration = Instant::now() - start_parse; // Calculate elapsed time.
+
+    let start_process = Instant::now(); // Start measuring time.
+
+    let (distance, blocking_cell) = solve(&coords, size, part1_generation);
+    println!("Part 1 = {}", distance);
+    println!("Part 2: {},{}", blocking_cell.0, blocking_cell.1);
+
     let elapsed_process: Duration = Instant::now() - start_process; // Calculate elapsed time.
 
-    eprintln!("Time taken for parsing: {:?}", elapsed_parse);
-    eprintln!("Time taken for processing: {:?}", elapsed_process);
-    eprintln!("Total time: {:?}", elapsed_process + elapsed_parse);
+    if !aoc::args::quiet() {
+        eprintln!("Time taken for parsing: {:?}", elapsed_parse);
+        eprintln!("Time taken for processing: {:?}", elapsed_process);
+        eprintln!("Total time: {:?}", elapsed_process + elapsed_parse);
+    }
+}
+
+#[test]
+fn check_sample() {
+    let sample = "5,4 4,2 4,5 3,0 2,1 6,3 2,4 1,5 0,6 3,3 2,6 5,1 1,2 \
+                  5,5 2,5 6,5 1,4 0,4 6,4 1,1 6,1 1,0 0,5 1,6 2,0";
+    let coords: Vec<(usize, usize)> = sample
+        .split(' ')
+        .map(|c| {
+            let (x, y) = c.split_once(',').unwrap();
+            (usize::from_str(x).unwrap(), usize::from_str(y).unwrap())
+        })
+        .collect();
+
+    assert_eq!(solve(&coords, 7, 12), (22, (6, 1)));
+    // Part 2 doesn't depend on the part 1 generation
+    assert_eq!(solve(&coords, 7, 20).1, (6, 1));
 }
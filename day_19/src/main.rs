@@ -4,8 +4,7 @@ https://adventofcode.com/2024/day/19
 
  */
 
-use regex::Regex;
-use std::collections::HashMap;
+use aoc::strings::{is_tileable, tiling_count};
 use std::io;
 use std::io::prelude::*;
 
@@ -33,42 +32,14 @@ use std::io::prelude::*;
 // extension required would be A+1 to A+6  to cover all possible
 // cases.
 
-// Return the total number of combinations of strings from substr[]
-// that concatenate exactly into "p".
-// This is the number of leaves of the tree where each node branch
-// is one element of substr, where the path concat equals to p.
-// Rust: hashmap of &str requires lifetime consistency. Would be simpler
-// with HashMap<String> with duplicated data; here we know that we always reference
-// slices of our starting string so lifetime annotation is manageable.
-fn combination_count<'a>(p: &'a str, substr: &[&str], memo: &mut HashMap<&'a str, usize>) -> usize {
-    if p.len() == 0 {
-        // Leaf found, the stack reaching it equals 1 possible
-        // combination
-        return 1;
-    }
-
-    if let Some(c) = memo.get(p) {
-        return *c;
-    }
-
-    let mut count = 0;
-    for s in substr {
-        if let Some(sub_p) = p.strip_prefix(s) {
-            count += combination_count(sub_p, substr, memo);
-        }
-    }
-
-    memo.insert(p, count);
-    count
-}
-
 fn count_all_possible_combinations(p: &Vec<String>, substr: &Vec<String>) -> usize {
     let substr: Vec<&str> = substr.iter().map(|s| s.as_str()).collect();
     let mut count = 0;
     for pat in p {
-        let mut memo = HashMap::<&str, usize>::new();
-        let single_count = combination_count(pat, &substr, &mut memo);
-        eprintln!("{pat} has {single_count} combinations");
+        let single_count = tiling_count(pat, &substr);
+        if aoc::args::is_debug() {
+            eprintln!("{pat} has {single_count} combinations");
+        }
         count += single_count;
     }
 
@@ -89,17 +60,11 @@ fn main() {
         patterns.push(line);
     }
 
-    // Construct the regex match dynamically from the towels stripes
-    // Something like "^(rg|wub|rr)*$".
-    // We suppose that the input is simple letters and will not need
-    // to validate input.
-    let reg: String = "^(".to_string() + &towels.join("|") + ")*$";
-    let re = Regex::new(&reg).unwrap();
-
-    eprintln!("Matching with regex:");
-    eprintln!("{reg}");
-
-    let matching = patterns.iter().filter(|p| re.is_match(p)).count();
+    let towels_str: Vec<&str> = towels.iter().map(|s| s.as_str()).collect();
+    let matching = patterns
+        .iter()
+        .filter(|p| is_tileable(p, &towels_str))
+        .count();
 
     println!("Part 1 = {:?}", matching);
 
@@ -3,7 +3,9 @@ https://adventofcode.com/2024/day/20
 --- Day 20: Race Condition ---
  */
 
-use aoc::grid::{Grid, GridBuilder};
+use aoc::args;
+use aoc::grid::Grid;
+use aoc::parse::parse_grid_with_markers;
 use std::io;
 use std::io::prelude::*;
 
@@ -12,36 +14,16 @@ use std::io::prelude::*;
 // grid point.
 // Start point starts at time '0', End points receives total time + 1,
 // and unpassable walls stay at 0.
-fn map_to_track_time(m: &Grid<char>, start: (usize, usize), end: (usize, usize)) -> Grid<usize> {
-    let mut track = Grid::<usize>::new(m.width, m.height, 0);
-    let mut pos = start;
-    let mut time = 1;
-    track.set(pos.0, pos.1, time);
-
-    let dir: [(isize, isize); 4] = [(0, 1), (0, -1), (1, 0), (-1, 0)];
-    // Don't backtrack (avoid checking the value in track[],
-    let mut coming_from: (isize, isize) = (0, 0);
-    while pos != end {
-        time += 1;
-        for d in &dir {
-            if *d == coming_from {
-                continue;
-            }
-            let next = (
-                pos.0.checked_add_signed(d.0).unwrap(),
-                pos.1.checked_add_signed(d.1).unwrap(),
-            );
-            if m.get(next.0, next.1) == '#' {
-                // wall
-                continue;
-            }
-            track.set(next.0, next.1, time);
-            pos = next;
-            coming_from = (-d.0, -d.1);
-            break;
-            // We expect the input to be well formed and always
-            // reach the exit, else infinite loop
-        }
+fn map_to_track_time(
+    walls: &Grid<bool>,
+    start: (usize, usize),
+    end: (usize, usize),
+) -> Grid<usize> {
+    let mut track = Grid::<usize>::new(walls.width, walls.height, 0);
+    // We expect the input to be well formed and always
+    // reach the exit through a single path.
+    for (time, pos) in walls.follow_single_path(start, end).into_iter().enumerate() {
+        track.set(pos.0, pos.1, time + 1);
     }
 
     track
@@ -172,40 +154,26 @@ fn find_super_cheat_cuts(track: &Grid<usize>, max_cheat: usize, min_time: usize)
 }
 
 fn main() {
-    let mut mapbuild = GridBuilder::<char>::new();
-
-    let mut start: (usize, usize) = (0, 0);
-    let mut end: (usize, usize) = (0, 0);
-
-    let mut lines = io::stdin().lock().lines();
-    let mut y = 0;
-    while let Some(Ok(line)) = lines.next() {
-        let mut vs: Vec<char> = line.chars().collect();
-        if let Some(s) = vs.iter().position(|&c| c == 'S') {
-            start = (s, y);
-            vs[s] = '.';
-        }
-        if let Some(e) = vs.iter().position(|&c| c == 'E') {
-            end = (e, y);
-            vs[e] = '.';
-        }
-        mapbuild.append_line(&vs);
-        y += 1;
+    let lines: Vec<String> = io::stdin().lock().lines().map_while(Result::ok).collect();
+    let (map, markers) = parse_grid_with_markers(&lines, &['S', 'E']);
+    let start = markers[&'S'][0];
+    let end = markers[&'E'][0];
+    if args::is_verbose() {
+        map.pretty_print();
+    }
+    if args::is_debug() {
+        eprintln!("Starts at {:?}, ends at {:?}", start, end);
     }
 
-    let map = mapbuild.to_grid();
-    map.pretty_print();
-    eprintln!("Starts at {:?}, ends at {:?}", start, end);
-
-    let track = map_to_track_time(&map, start, end);
-    track.pretty_print_lambda(&|d: usize| {
-        if d == 0 {
-            ". ".to_string()
-        } else {
-            format!("{} ", d % 10)
-        }
-    });
-    eprintln!("Total track time is {}", track.get(end.0, end.1) - 1);
+    let walls = map.to_bool('#');
+    let track = map_to_track_time(&walls, start, end);
+    let total_time = track.get(end.0, end.1);
+    if args::is_verbose() {
+        track.pretty_print_heat(1, total_time);
+    }
+    if args::is_debug() {
+        eprintln!("Total track time is {}", total_time - 1);
+    }
 
     // different settings for sample and real input
     let pico_to_save = if track.width > 15 { 100 } else { 15 };
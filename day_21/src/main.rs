@@ -249,12 +249,17 @@ fn main() {
     let nmap = get_numeric_keypad_map();
     let dmap = get_directional_keypad_map();
 
+    let debug = aoc::args::is_debug();
     for code in codes {
         let num = extract_numeric(&code);
         let robot1_door = keypad_code_to_directions(&code, &nmap);
-        eprintln!("{num} => {:?}", robot1_door);
+        if debug {
+            eprintln!("{num} => {:?}", robot1_door);
+        }
         let robot2_radiation = sequences_to_directions(&robot1_door, &dmap);
-        eprintln!(" => {:?}", robot2_radiation);
+        if debug {
+            eprintln!(" => {:?}", robot2_radiation);
+        }
         //let robot3_freezer = keypad_code_to_directions(&robot2_radiation, &dmap);
         //let human4 = keypad_code_to_directions(&robot3_freezer, &dmap);
     }
@@ -3,28 +3,42 @@ https://adventofcode.com/2024/day/22
 --- Day 22: Monkey Market ---
 */
 
-use std::collections::{HashMap, HashSet};
+use aoc::util::encode_base;
+use std::collections::HashSet;
 use std::io;
 use std::io::prelude::*;
 use std::str::FromStr;
 
+// Secrets are always "pruned" modulo 16777216 = 2^24
+const PRUNE_MODULUS: u64 = 1 << 24;
+const PRUNE_MASK: u64 = PRUNE_MODULUS - 1;
+
 // Perform 1 round of RNG
+// The modulus being a power of two, pruning is done by masking.
+// Intermediate values are computed on u64: after each pruning
+// they are < 2^24, and the largest product (n * 2048) is < 2^35
+// which would overflow a 32-bit usize.
+#[inline]
 fn iter_pseudorand(n: usize) -> usize {
-    let n = ((n * 64) ^ n) % 16777216;
-    let n = ((n / 32) ^ n) % 16777216;
-    let n = ((n * 2048) ^ n) % 16777216;
+    let n = n as u64;
+    let n = ((n << 6) ^ n) & PRUNE_MASK;
+    let n = ((n >> 5) ^ n) & PRUNE_MASK;
+    let n = ((n << 11) ^ n) & PRUNE_MASK;
 
-    n
+    n as usize
 }
 
-fn sum_2000_secrets(buyer_secrets: &Vec<usize>) -> usize {
+fn sum_secrets(buyer_secrets: &Vec<usize>, iterations: usize) -> usize {
+    let debug = aoc::args::is_debug();
     let mut total = 0;
     for s in buyer_secrets {
         let mut i = *s;
-        for _ in 0..2000 {
+        for _ in 0..iterations {
             i = iter_pseudorand(i);
         }
-        eprintln!("2000th iter of {s} is {i}");
+        if debug {
+            eprintln!("{iterations}th iter of {s} is {i}");
+        }
         total += i;
     }
     total
@@ -61,6 +75,9 @@ fn get_sequences(changes: &Vec<isize>) -> Vec<[isize; 4]> {
 }
  */
 
+// Number of possible 4-changes sequences, each change being in -9..=9
+const SEQUENCE_KEYS: usize = 19 * 19 * 19 * 19;
+
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
 struct SequenceAndPrice {
     seq: [isize; 4],
@@ -106,7 +123,10 @@ fn keep_first_sequence_occurences(sp: &Vec<SequenceAndPrice>) -> Vec<SequenceAnd
 }
 
 fn find_best_common_sequence(buyers: &[usize]) -> usize {
-    eprintln!("starting pre-processing");
+    let debug = aoc::args::is_debug();
+    if debug {
+        eprintln!("starting pre-processing");
+    }
     let buyers_prices: Vec<Vec<usize>> = buyers.iter().map(|b| get_2000_prices(*b)).collect();
     let buyers_sequences: Vec<Vec<SequenceAndPrice>> = buyers_prices
         .iter()
@@ -119,12 +139,14 @@ fn find_best_common_sequence(buyers: &[usize]) -> usize {
 
     /* XXX FIXME TODO */
 
-    eprintln!("pre-processing done");
-    let mut total_first_seq = 0;
-    for bs in &buyers_sequences {
-        total_first_seq += bs.len();
+    if debug {
+        eprintln!("pre-processing done");
+        let mut total_first_seq = 0;
+        for bs in &buyers_sequences {
+            total_first_seq += bs.len();
+        }
+        eprintln!("Filtered first-sequences count = {total_first_seq}");
     }
-    eprintln!("Filtered first-sequences count = {total_first_seq}");
 
     // There is no more than 18^4 different [isize;4] ranging from -9 to +9; actually a lot less
     // before their final sum from a starting 0..9 price must also be in 0..9, so any
@@ -151,18 +173,19 @@ fn find_best_common_sequence(buyers: &[usize]) -> usize {
 
      */
 
-    // Lets put everyting into a hashmap with their total cumulated prices.
+    // Lets put everyting into a flat array with their total cumulated prices,
+    // indexed by the sequence encoded in base 19 (changes are -9..=9).
 
-    // Hash to tuple of (number of buyers, total price). Number of buyers is just for debug.
-    let mut cumulative_bidding = HashMap::<[isize; 4], (usize, usize)>::new();
+    // Tuple of (number of buyers, total price). Number of buyers is just for debug.
+    let mut cumulative_bidding = vec![(0usize, 0usize); SEQUENCE_KEYS];
 
-    // Maintain the best result, no need to sort() the hashmap at the end.
+    // Maintain the best result, no need to sort() the array at the end.
     let mut max_price = 0;
     let mut max_sequence: [isize; 4] = [0, 0, 0, 0];
     let mut bidders = 0;
 
     for k in buyers_sequences.iter().flatten() {
-        let count_price = cumulative_bidding.entry(k.seq).or_default();
+        let count_price = &mut cumulative_bidding[encode_base(&k.seq, 9, 19)];
         (*count_price).0 += 1;
         (*count_price).1 += k.price;
         if (*count_price).1 > max_price {
@@ -175,10 +198,12 @@ fn find_best_common_sequence(buyers: &[usize]) -> usize {
     // This gives the correct result for official problem Input.
     // But not for the simpler sample !!
 
-    eprintln!(
-        "Max seq/price = {:?} => {max_price} ({bidders} different buyers)",
-        max_sequence
-    );
+    if debug {
+        eprintln!(
+            "Max seq/price = {:?} => {max_price} ({bidders} different buyers)",
+            max_sequence
+        );
+    }
 
     max_price
 }
@@ -192,7 +217,9 @@ fn main() {
         buyer_secrets.push(secret);
     }
 
-    println!("Part 1 = {}", sum_2000_secrets(&buyer_secrets));
+    // Number of iterations can be changed with --steps=N
+    let iterations = aoc::args::arg_value("steps").unwrap_or(2000);
+    println!("Part 1 = {}", sum_secrets(&buyer_secrets, iterations));
     // for dev speedup use only a subset of buyers
     //let buyer_secrets = &buyer_secrets[0..100];
     println!("Part 2 = {}", find_best_common_sequence(&buyer_secrets));
@@ -210,3 +237,31 @@ fn check_basic_rng() {
         assert_eq!(s, k);
     }
 }
+
+#[test]
+fn check_sum_secrets() {
+    let buyers = vec![1, 10, 100, 2024];
+    assert_eq!(sum_secrets(&buyers, 2000), 37327623);
+    assert_eq!(sum_secrets(&vec![123], 10), 5908254);
+}
+
+#[test]
+fn check_masked_rng_matches_modulo() {
+    // Reference implementation straight from the puzzle text
+    fn iter_pseudorand_modulo(n: u64) -> u64 {
+        let n = ((n * 64) ^ n) % 16777216;
+        let n = ((n / 32) ^ n) % 16777216;
+        ((n * 2048) ^ n) % 16777216
+    }
+
+    for seed in [0, 1, 10, 100, 2024, 123, 16777215] {
+        let mut s = seed;
+        let mut r = seed as u64;
+        for _ in 0..2000 {
+            s = iter_pseudorand(s);
+            r = iter_pseudorand_modulo(r);
+            assert_eq!(s as u64, r);
+            assert!((s as u64) < PRUNE_MODULUS);
+        }
+    }
+}
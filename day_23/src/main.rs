@@ -3,7 +3,10 @@ https://adventofcode.com/2024/day/23
 --- Day 23: LAN Party ---
 */
 
+use aoc::args;
 use aoc::grid::Grid;
+use aoc::parse::parse_edges;
+use aoc::util::hamming_distance;
 use std::collections::HashMap;
 use std::io;
 use std::io::prelude::*;
@@ -50,56 +53,34 @@ fn tuples_of_3_computers(matrix: &Grid<bool>) -> Vec<(usize, usize, usize)> {
     result
 }
 
-// Between two row of the adjacency matrix,
-// return the number of elements (columns) where they differ.
-fn get_row_distance<T: std::cmp::PartialEq>(a: &[T], b: &[T]) -> usize {
-    let mut diff = 0;
-    if a.len() != b.len() {
-        panic!("Can't compare slices of different len");
-    }
-
-    for k in 0..a.len() {
-        if a[k] != b[k] {
-            diff += 1;
-        }
-    }
-
-    diff
+// Number of links of each computer, by name.
+// Funny inputs... they all have the exact same connectivity count:
+// 4 for the sample, 13 for the problem input.
+fn node_degrees(matrix: &Grid<bool>, names: &[String]) -> HashMap<String, usize> {
+    names
+        .iter()
+        .enumerate()
+        .map(|(a, name)| {
+            (
+                name.clone(),
+                matrix.get_row_slice(a).iter().filter(|l| **l).count(),
+            )
+        })
+        .collect()
 }
 
 fn find_biggest_tuple(matrix: &Grid<bool>, names: &Vec<String>) -> Vec<usize> {
     let m = matrix.width;
 
-    // Count how much connectivy each node has
-    // XXX Funny inputs... they all have the exact same connectivity count.
-    // 4 for the sample, 13 for the problem input.
-    /*
-        let mut connectivy_count = Vec::<usize>::with_capacity(matrix.width);
-
-
-
-        for a in 0..m {
-            let mut connect = 0;
-            for b in 0..m {
-                if matrix.get(a,b) {
-                    connect += 1;
-                }
-            }
-            connectivy_count.push(connect);
-        }
-
-        let mut max_connectivity = connectivy_count.clone();
-        max_connectivity.sort();
-
-        eprintln!("Connects = {:?}", max_connectivity);
-    */
     // Add the diagonal (self-connectivity) for easier processing
     let mut matrix = matrix.clone();
     for a in 0..m {
         matrix.set(a, a, true);
     }
 
-    matrix.pretty_print_bool();
+    if args::is_verbose() {
+        matrix.pretty_print_bool();
+    }
 
     // We suppose that the biggest connected group will be connected
     // only to itself, except for one outside connection for each member
@@ -111,6 +92,7 @@ fn find_biggest_tuple(matrix: &Grid<bool>, names: &Vec<String>) -> Vec<usize> {
     // (Initially the assumption was that the group did not have any
     // external connection at all but this failed)
 
+    let debug = args::is_debug();
     'search: for a in 0..m {
         let row_a = matrix.get_row_slice(a);
         let mut outliers = 0;
@@ -121,10 +103,12 @@ fn find_biggest_tuple(matrix: &Grid<bool>, names: &Vec<String>) -> Vec<usize> {
             if a != b && row_a[b] {
                 // a and b are connected
                 let row_b = matrix.get_row_slice(b);
-                let diff = get_row_distance(row_a, row_b);
+                let diff = hamming_distance(row_a, row_b);
                 let na = &names[a];
                 let nb = &names[b];
-                eprintln!("diff {a}/{b} ({na}/{nb}) = {diff}");
+                if debug {
+                    eprintln!("diff {a}/{b} ({na}/{nb}) = {diff}");
+                }
                 // We accept at most 1 difference in the group (double it
                 // because if some ma in a is missing in b,
                 // then another mb in b is missing in a too.)
@@ -136,7 +120,9 @@ fn find_biggest_tuple(matrix: &Grid<bool>, names: &Vec<String>) -> Vec<usize> {
                     }
                 } else {
                     group.push(b);
-                    eprintln!("{a} and {b} are similar");
+                    if debug {
+                        eprintln!("{a} and {b} are similar");
+                    }
                 }
             }
         }
@@ -148,15 +134,14 @@ fn find_biggest_tuple(matrix: &Grid<bool>, names: &Vec<String>) -> Vec<usize> {
     panic!("Error: did not find any stable group");
 }
 
-fn main() {
+// Returns the computer names index, its reverse, and
+// the adjacency matrix.
+fn parse_network(lines: &[String]) -> (HashMap<String, usize>, Vec<String>, Grid<bool>) {
     let mut computers = HashMap::<String, usize>::new();
     let mut computers_names = Vec::<String>::new(); // reverse of hash
     let mut netmap = Vec::<(usize, usize)>::new();
 
-    let mut lines = io::stdin().lock().lines();
-    while let Some(Ok(line)) = lines.next() {
-        let (a, b) = line.split_once("-").unwrap();
-        let (a, b) = (a.to_string(), b.to_string());
+    for (a, b) in parse_edges(lines, '-') {
         let a_idx;
         let b_idx;
         // nightly try_insert() would be simpler here.
@@ -187,7 +172,24 @@ fn main() {
     // This is a very sparse matrix, not sure if it's more efficient
     // than just comparing a linear list...
 
-    matrix.pretty_print_bool();
+    (computers, computers_names, matrix)
+}
+
+fn main() {
+    let lines: Vec<String> = io::stdin().lock().lines().map_while(Result::ok).collect();
+    let (computers, computers_names, matrix) = parse_network(&lines);
+
+    if args::is_verbose() {
+        matrix.pretty_print_bool();
+    }
+
+    if args::is_debug() {
+        let mut degrees: Vec<usize> = node_degrees(&matrix, &computers_names)
+            .into_values()
+            .collect();
+        degrees.sort();
+        eprintln!("Connects = {:?}", degrees);
+    }
 
     let t_computers = indices_of_t_computers(&computers);
     let triplets = tuples_of_3_computers(&matrix);
@@ -203,23 +205,44 @@ fn main() {
         // we COULD do it just by triplet.starts_with('t') || triplets.contains(",t") ...
         if t_computers.contains(&k.0) || t_computers.contains(&k.1) || t_computers.contains(&k.2) {
             count_triplet_with_t += 1;
-            eprintln!("Found {}", triplet);
+            if args::is_debug() {
+                eprintln!("Found {}", triplet);
+            }
         }
     }
 
     println!("Part 1 = {count_triplet_with_t}");
 
-    eprintln!("Names index = {:?}", computers_names);
+    if args::is_debug() {
+        eprintln!("Names index = {:?}", computers_names);
+    }
 
     let max_tuple = find_biggest_tuple(&matrix, &computers_names);
-    eprintln!("Biggest tuple is {:?}", max_tuple);
+    if args::is_debug() {
+        eprintln!("Biggest tuple is {:?}", max_tuple);
+    }
     let mut names: Vec<String> = max_tuple
         .iter()
         .map(|i| computers_names[*i].clone())
         .collect();
     names.sort();
-    eprintln!("Names = {:?}", names);
+    if args::is_debug() {
+        eprintln!("Names = {:?}", names);
+    }
 
     let password: String = names.join(",");
     println!("Part 2 = {password}");
 }
+
+#[test]
+fn check_node_degrees() {
+    let lines: Vec<String> = include_str!("../sample.txt")
+        .lines()
+        .map(String::from)
+        .collect();
+    let (_, names, matrix) = parse_network(&lines);
+    let degrees = node_degrees(&matrix, &names);
+    assert_eq!(degrees.len(), 16);
+    assert!(degrees.values().all(|d| *d == 4));
+    assert_eq!(degrees["ka"], 4);
+}
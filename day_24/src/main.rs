@@ -4,39 +4,18 @@ https://adventofcode.com/2024/day/24
 
  */
 
+use aoc::graph::{topological_sort, Graph};
+use aoc::logic::{check_full_adder, Gate, LogicalOp};
+use aoc::parse::parse_kv_block;
 use std::collections::HashMap;
 use std::io;
 use std::io::prelude::*;
 use std::str::FromStr;
 
-#[derive(Clone, Debug)]
-enum LogicalOp {
-    And,
-    Or,
-    Xor,
-}
-
-impl LogicalOp {
-    fn compute(&self, in1: u8, in2: u8) -> u8 {
-        match self {
-            LogicalOp::And => in1 & in2,
-            LogicalOp::Or => in1 | in2,
-            LogicalOp::Xor => in1 ^ in2,
-        }
-    }
-}
-
-#[derive(Clone, Debug)]
-struct Gate {
-    in1: String,
-    in2: String,
-    out: String,
-    op: LogicalOp,
-}
-
 // Update the signals/gates by 1 tick (sometimes a bit more
 // if signals cascade on next gate in the loop order).
 // Returns the list of still undefined gates (speedup test)
+#[cfg(test)]
 fn propagate_signal_once(wires: &mut HashMap<String, u8>, gates: &Vec<Gate>) -> Vec<Gate> {
     let mut remain = Vec::<Gate>::new();
     for g in gates {
@@ -61,14 +40,59 @@ fn propagate_signal_once(wires: &mut HashMap<String, u8>, gates: &Vec<Gate>) ->
 }
 
 // Update the signals/gates until no signal remains undefined.
+// Reference version for the tests, see propagate_signal_sorted.
+#[cfg(test)]
 fn propagate_signal(wires: &mut HashMap<String, u8>, gates: &Vec<Gate>) {
     let mut working_gates = gates.clone();
 
-    while working_gates.len() != 0 {
+    while !working_gates.is_empty() {
         working_gates = propagate_signal_once(wires, &working_gates);
     }
 }
 
+// Order the gates so that each one comes after the gates
+// computing its inputs, then evaluate them all in a single pass.
+// If the wiring has a loop, returns the output wires of the
+// gates that could not be ordered.
+fn propagate_signal_sorted(
+    wires: &mut HashMap<String, u8>,
+    gates: &[Gate],
+) -> Result<(), Vec<String>> {
+    let producers: HashMap<&str, usize> = gates
+        .iter()
+        .enumerate()
+        .map(|(i, g)| (g.out.as_str(), i))
+        .collect();
+
+    // Edges from the gate producing a wire to the gates using it.
+    let mut deps = Graph::<usize>::new();
+    for (i, g) in gates.iter().enumerate() {
+        deps.add_node(i);
+        for input in [&g.in1, &g.in2] {
+            if let Some(&p) = producers.get(input.as_str()) {
+                deps.add_edge(p, i, 1);
+            }
+        }
+    }
+
+    let order = topological_sort(&deps).map_err(|remain| {
+        remain
+            .into_iter()
+            .map(|i| gates[i].out.clone())
+            .collect::<Vec<_>>()
+    })?;
+
+    for i in order {
+        let g = &gates[i];
+        let out = g.op.compute(wires[&g.in1], wires[&g.in2]);
+        if wires.insert(g.out.clone(), out).is_some() {
+            panic!("Wire was already computed");
+        }
+    }
+
+    Ok(())
+}
+
 fn parse_z_wires(wires: &HashMap<String, u8>) -> usize {
     // We don't really know or care in advance how many zxx wires
     // were defined.
@@ -86,24 +110,17 @@ fn parse_z_wires(wires: &HashMap<String, u8>) -> usize {
     res
 }
 
-fn main() {
+fn parse_input(mut lines: impl Iterator<Item = String>) -> (HashMap<String, u8>, Vec<Gate>) {
     let mut wires = HashMap::<String, u8>::new();
     let mut gates = Vec::<Gate>::new();
 
-    let mut lines = io::stdin().lock().lines();
-
-    while let Some(Ok(line)) = lines.next() {
-        if line.len() == 0 {
-            break;
-        }
-
-        let (name, val) = line.split_once(": ").unwrap();
-        let name = name.to_string();
-        let val = u8::from_str(val).unwrap();
-        wires.insert(name, val);
+    let initial: Vec<String> = lines.by_ref().take_while(|l| !l.is_empty()).collect();
+    let initial: Vec<&str> = initial.iter().map(|l| l.as_str()).collect();
+    for (name, val) in parse_kv_block(&initial, ": ") {
+        wires.insert(name, u8::from_str(&val).unwrap());
     }
 
-    while let Some(Ok(line)) = lines.next() {
+    for line in lines {
         let (gate, out) = line.split_once(" -> ").unwrap();
         let out = out.to_string();
         let gate: Vec<&str> = gate.split(" ").collect();
@@ -122,15 +139,91 @@ fn main() {
         gates.push(Gate { in1, in2, out, op });
     }
 
+    (wires, gates)
+}
+
+// A N-bit adder has N xNN and yNN input wires, and N+1 zNN outputs
+// (the last one being the final carry).
+// Return N, or None if the circuit doesn't have this shape.
+fn adder_width(wires: &HashMap<String, u8>, gates: &[Gate]) -> Option<usize> {
+    let x_count = wires.keys().filter(|w| w.starts_with('x')).count();
+    let y_count = wires.keys().filter(|w| w.starts_with('y')).count();
+    let z_count = gates.iter().filter(|g| g.out.starts_with('z')).count();
+    (x_count > 0 && x_count == y_count && z_count == x_count + 1).then_some(x_count)
+}
+
+fn main() {
+    let (wires, gates) = parse_input(io::stdin().lock().lines().map_while(Result::ok));
+
     /*
-    eprintln!("Parsed initial wires: {:?}", wires);
-    eprintln!("Parsed gates: {:?}", gates);
+    if aoc::args::is_debug() {
+        eprintln!("Parsed initial wires: {:?}", wires);
+        eprintln!("Parsed gates: {:?}", gates);
+    }
      */
 
     let mut working_wires = wires.clone();
 
-    propagate_signal(&mut working_wires, &gates);
+    propagate_signal_sorted(&mut working_wires, &gates)
+        .unwrap_or_else(|w| panic!("Gates are looping on wires {:?}", w));
     //eprintln!("Final wires values: {:?}", working_wires);
     let final_z = parse_z_wires(&working_wires);
     println!("Part 1 = {final_z}");
+
+    // Only meaningful for the real input, the samples are not adders.
+    if adder_width(&wires, &gates).is_some() {
+        let swapped = check_full_adder(&gates);
+        println!("Part 2 = {}", swapped.join(","));
+    } else {
+        println!("Part 2 = (not an adder)");
+    }
+}
+
+#[cfg(test)]
+fn check_sorted_same_as_iterative(input: &str) -> usize {
+    let (wires, gates) = parse_input(input.lines().map(String::from));
+
+    let mut iterative = wires.clone();
+    propagate_signal(&mut iterative, &gates);
+    let mut sorted = wires;
+    propagate_signal_sorted(&mut sorted, &gates).unwrap();
+
+    assert_eq!(sorted, iterative);
+    parse_z_wires(&sorted)
+}
+
+#[test]
+fn check_sorted_propagation() {
+    assert_eq!(
+        check_sorted_same_as_iterative(include_str!("../sample.txt")),
+        4
+    );
+    assert_eq!(
+        check_sorted_same_as_iterative(include_str!("../sample2.txt")),
+        2024
+    );
+}
+
+#[test]
+fn check_adder_width() {
+    for sample in [
+        include_str!("../sample.txt"),
+        include_str!("../sample2.txt"),
+    ] {
+        let (wires, gates) = parse_input(sample.lines().map(String::from));
+        assert_eq!(adder_width(&wires, &gates), None);
+    }
+
+    let adder = "x00: 1\ny00: 1\n\nx00 XOR y00 -> z00\nx00 AND y00 -> z01";
+    let (wires, gates) = parse_input(adder.lines().map(String::from));
+    assert_eq!(adder_width(&wires, &gates), Some(1));
+}
+
+#[test]
+fn check_sorted_propagation_loop() {
+    let input = "x00: 1\ny00: 0\n\nx00 AND aaa -> bbb\nbbb OR y00 -> aaa\nx00 XOR y00 -> z00";
+    let (mut wires, gates) = parse_input(input.lines().map(String::from));
+    let mut remain = propagate_signal_sorted(&mut wires, &gates).unwrap_err();
+    remain.sort();
+    assert_eq!(remain, vec!["aaa", "bbb"]);
 }
@@ -12,6 +12,8 @@ struct Pins {
     height: [isize; 5],
 }
 
+// Reference implementation, checking each column
+#[cfg(test)]
 fn check_fit(key: &Pins, lock: &Pins) -> bool {
     for i in 0..5 {
         if key.height[i] + lock.height[i] > 5 {
@@ -22,6 +24,7 @@ fn check_fit(key: &Pins, lock: &Pins) -> bool {
     true
 }
 
+#[cfg(test)]
 fn count_fitting_pairs(keys: &Vec<Pins>, locks: &Vec<Pins>) -> usize {
     let mut pairs = 0;
 
@@ -37,6 +40,35 @@ fn count_fitting_pairs(keys: &Vec<Pins>, locks: &Vec<Pins>) -> usize {
     pairs
 }
 
+// Encode the 5x5 interior of a lock or key as a 25 bits set,
+// 5 bits per column, where bit n of a column is set if row n is
+// occupied by a pin.
+// A lock pin of height h fills the rows 0..h from the top,
+// a key pin fills the rows 5-h..5 from the bottom, so
+// a key fits in a lock if the two sets don't intersect.
+const PIN_ROWS: isize = 5;
+
+fn lock_bits(lock: &Pins) -> u32 {
+    lock.height.iter().enumerate().fold(0, |bits, (i, h)| {
+        bits | ((1 << h) - 1) << (i as isize * PIN_ROWS)
+    })
+}
+
+fn key_bits(key: &Pins) -> u32 {
+    key.height.iter().enumerate().fold(0, |bits, (i, h)| {
+        bits | ((1 << h) - 1) << (PIN_ROWS - h + i as isize * PIN_ROWS)
+    })
+}
+
+fn count_fitting_pairs_bits(keys: &[Pins], locks: &[Pins]) -> usize {
+    let lock_sets: Vec<u32> = locks.iter().map(lock_bits).collect();
+
+    keys.iter()
+        .map(key_bits)
+        .map(|k| lock_sets.iter().filter(|l| *l & k == 0).count())
+        .sum()
+}
+
 // Parse stdio and returns a vec of keys
 // and a vec of locks.
 fn parse_input() -> (Vec<Pins>, Vec<Pins>) {
@@ -101,8 +133,30 @@ fn parse_input() -> (Vec<Pins>, Vec<Pins>) {
 fn main() {
     let (keys, locks) = parse_input();
 
-    eprintln!("Parsed locks: {:?}", locks);
-    eprintln!("Parsed keyss: {:?}", keys);
+    if aoc::args::is_debug() {
+        eprintln!("Parsed locks: {:?}", locks);
+        eprintln!("Parsed keyss: {:?}", keys);
+    }
+
+    println!("Part 1 = {}", count_fitting_pairs_bits(&keys, &locks));
+}
 
-    println!("Part 1 = {}", count_fitting_pairs(&keys, &locks));
+#[test]
+fn check_bits_match_loop() {
+    let pins = |height| Pins { height };
+    let locks = vec![pins([0, 5, 3, 4, 3]), pins([1, 2, 0, 5, 3])];
+    let keys = vec![
+        pins([5, 0, 2, 1, 3]),
+        pins([4, 3, 4, 0, 2]),
+        pins([3, 0, 2, 0, 1]),
+    ];
+
+    assert_eq!(count_fitting_pairs(&keys, &locks), 3);
+    assert_eq!(count_fitting_pairs_bits(&keys, &locks), 3);
+
+    for k in &keys {
+        for l in &locks {
+            assert_eq!(check_fit(k, l), key_bits(k) & lock_bits(l) == 0);
+        }
+    }
 }